@@ -0,0 +1,87 @@
+//! Keystroke latency on a large synthetic catalog.
+//!
+//! Run with `cargo bench -p snotra-core --bench search`.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use snotra_core::history::HistoryStore;
+use snotra_core::indexer::{AppEntry, EntryKind};
+use snotra_core::search::{SearchEngine, SearchMode};
+
+const ENTRIES: usize = 200_000;
+
+const WORDS: &[&str] = &[
+    "visual", "studio", "code", "chrome", "firefox", "report", "invoice", "setup", "notes",
+    "photo", "backup", "project", "server", "client", "editor", "viewer", "manager", "tool",
+    "メモ", "設定", "資料", "写真",
+];
+const EXTENSIONS: &[&str] = &["exe", "lnk", "txt", "pdf", "xlsx", "png"];
+
+/// Deterministic names such as `Report Server 1234.pdf` under a few nested folders.
+fn synthetic_entries(count: usize) -> Vec<AppEntry> {
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize
+    };
+    (0..count)
+        .map(|i| {
+            let words = 1 + next() % 3;
+            let stem = (0..words)
+                .map(|_| WORDS[next() % WORDS.len()])
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ext = EXTENSIONS[next() % EXTENSIONS.len()];
+            let file_name = format!("{} {}.{}", stem, i, ext);
+            let target_path = format!(
+                "C:\\Users\\user\\{}\\{}\\{}",
+                WORDS[next() % WORDS.len()],
+                WORDS[next() % WORDS.len()],
+                file_name
+            );
+            AppEntry {
+                name: format!("{} {}", stem, i),
+                kind: EntryKind::for_path(&target_path, false),
+                target_path,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn bench_search(c: &mut Criterion) {
+    let entries = synthetic_entries(ENTRIES);
+    let history = HistoryStore::in_memory(10, 8);
+
+    c.bench_function("build 200k", |b| {
+        b.iter_batched(
+            || entries.clone(),
+            |entries| SearchEngine::new(black_box(entries)),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+
+    let engine = SearchEngine::new(entries);
+    // One benchmark per keystroke of a typical query
+    for (label, mode) in [
+        ("prefix", SearchMode::Prefix),
+        ("substring", SearchMode::Substring),
+        ("fuzzy", SearchMode::Fuzzy),
+    ] {
+        for query in ["r", "re", "rep", "repo", "report", "report.pdf", "資料"] {
+            c.bench_function(&format!("{} {:?}", label, query), |b| {
+                b.iter(|| engine.search(black_box(query), 8, &history, mode))
+            });
+        }
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = bench_search
+}
+criterion_main!(benches);
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Handle of a string in a `StrArena`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct StrId(u32);
+
+/// Strings stored back to back in one buffer, so a large catalog costs one allocation
+/// instead of one per string. Equal strings are stored once.
+#[derive(Debug, Default)]
+pub(crate) struct StrArena {
+    buf: String,
+    spans: Vec<(u32, u32)>,
+    /// String hash -> id, to find duplicates without keeping owned keys around.
+    /// Only used while building; a collision just stores the string again.
+    lookup: HashMap<u64, StrId>,
+}
+
+impl StrArena {
+    /// The empty string, present in every arena.
+    pub(crate) const EMPTY: StrId = StrId(0);
+
+    pub(crate) fn with_capacity(strings: usize, bytes: usize) -> Self {
+        let mut arena = Self {
+            buf: String::with_capacity(bytes),
+            spans: Vec::with_capacity(strings + 1),
+            lookup: HashMap::with_capacity(strings),
+        };
+        arena.spans.push((0, 0));
+        arena
+    }
+
+    pub(crate) fn intern(&mut self, s: &str) -> StrId {
+        if s.is_empty() {
+            return Self::EMPTY;
+        }
+        let mut hasher = DefaultHasher::new();
+        s.hash(&mut hasher);
+        let hash = hasher.finish();
+        if let Some(&id) = self.lookup.get(&hash)
+            && self.get(id) == s
+        {
+            return id;
+        }
+        let id = StrId(self.spans.len() as u32);
+        self.spans.push((self.buf.len() as u32, s.len() as u32));
+        self.buf.push_str(s);
+        self.lookup.insert(hash, id);
+        id
+    }
+
+    /// Drop the build-time lookup table and spare capacity.
+    pub(crate) fn finish(&mut self) {
+        self.lookup = HashMap::new();
+        self.buf.shrink_to_fit();
+        self.spans.shrink_to_fit();
+    }
+
+    pub(crate) fn get(&self, id: StrId) -> &str {
+        let (start, len) = self.spans[id.0 as usize];
+        &self.buf[start as usize..(start + len) as usize]
+    }
+}
+
+/// Approximate set of the characters in a string: one bit per ASCII letter and digit,
+/// other characters share the remaining bits. If a query has a character the candidate
+/// lacks, no search mode can match, so this rejects most entries before any scoring.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct CharSet(u64);
+
+impl CharSet {
+    /// Whitespace is left out: it never has to be present for a match.
+    pub(crate) fn of(s: &str) -> Self {
+        let mut set = Self::default();
+        set.extend(s);
+        set
+    }
+
+    pub(crate) fn extend(&mut self, s: &str) {
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            self.0 |= 1 << Self::bit(c);
+        }
+    }
+
+    pub(crate) fn contains_all(self, other: CharSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    fn bit(c: char) -> u32 {
+        match c {
+            'a'..='z' => c as u32 - 'a' as u32,
+            '0'..='9' => 26 + (c as u32 - '0' as u32),
+            _ => 36 + (c as u32 % 28),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arena_interns_equal_strings_once() {
+        let mut arena = StrArena::with_capacity(4, 32);
+        let a = arena.intern("chrome");
+        let b = arena.intern("github.com");
+        assert_eq!(arena.intern("chrome"), a);
+        assert_eq!(arena.intern(""), StrArena::EMPTY);
+        arena.finish();
+        assert_eq!(arena.get(a), "chrome");
+        assert_eq!(arena.get(b), "github.com");
+        assert_eq!(arena.get(StrArena::EMPTY), "");
+        assert_eq!(arena.buf.len(), "chromegithub.com".len());
+    }
+
+    #[test]
+    fn char_set_rejects_missing_characters() {
+        let name = CharSet::of("visual studio code 2022");
+        assert!(name.contains_all(CharSet::of("vsc")));
+        assert!(name.contains_all(CharSet::of("code 22")));
+        assert!(!name.contains_all(CharSet::of("vscx")));
+        assert!(CharSet::of("メモ帳").contains_all(CharSet::of("メモ")));
+        assert!(CharSet::of("anything").contains_all(CharSet::of("   ")));
+    }
+}
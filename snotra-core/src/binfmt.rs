@@ -9,11 +9,7 @@ pub fn serialize_with_header<T: Serialize>(
     payload: &T,
 ) -> Option<Vec<u8>> {
     let body = bincode::serialize(payload).ok()?;
-    let mut out = Vec::with_capacity(HEADER_LEN + body.len());
-    out.extend_from_slice(&magic);
-    out.extend_from_slice(&version.to_le_bytes());
-    out.extend_from_slice(&body);
-    Some(out)
+    Some(with_header(magic, version, &body))
 }
 
 pub fn deserialize_with_header<T: DeserializeOwned>(
@@ -21,6 +17,20 @@ pub fn deserialize_with_header<T: DeserializeOwned>(
     magic: [u8; 4],
     version: u32,
 ) -> Option<T> {
+    bincode::deserialize(payload(bytes, magic, version)?).ok()
+}
+
+/// Prefix an already encoded body with the header, for files with a custom layout.
+pub fn with_header(magic: [u8; 4], version: u32, body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + body.len());
+    out.extend_from_slice(&magic);
+    out.extend_from_slice(&version.to_le_bytes());
+    out.extend_from_slice(body);
+    out
+}
+
+/// The bytes after a matching header, without decoding them.
+pub fn payload(bytes: &[u8], magic: [u8; 4], version: u32) -> Option<&[u8]> {
     if bytes.len() < HEADER_LEN {
         return None;
     }
@@ -32,7 +42,7 @@ pub fn deserialize_with_header<T: DeserializeOwned>(
     if u32::from_le_bytes(ver) != version {
         return None;
     }
-    bincode::deserialize(&bytes[HEADER_LEN..]).ok()
+    Some(&bytes[HEADER_LEN..])
 }
 
 #[cfg(test)]
@@ -0,0 +1,152 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::indexer::{AppEntry, EntryKind};
+use crate::shortcut::UrlShortcut;
+
+/// Roots in the order the browser shows them; unknown roots follow in name order.
+const ROOT_ORDER: [&str; 3] = ["bookmark_bar", "other", "synced"];
+
+/// Schemes the shell cannot open from a search result.
+const SKIPPED_SCHEMES: [&str; 2] = ["javascript:", "data:"];
+
+#[derive(Deserialize)]
+struct BookmarkFile {
+    roots: HashMap<String, BookmarkNode>,
+}
+
+#[derive(Deserialize)]
+struct BookmarkNode {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    children: Vec<BookmarkNode>,
+}
+
+/// Read a Chromium-format `Bookmarks` file (Chrome, Edge, Brave, ...).
+pub fn read_bookmarks(path: &Path) -> Option<Vec<AppEntry>> {
+    let text = std::fs::read_to_string(path).ok()?;
+    parse_bookmarks(&text)
+}
+
+/// Every URL bookmark as a URL entry keyed by its URL.
+/// The folder path (e.g. `ブックマーク バー/Dev`) is kept as context.
+pub fn parse_bookmarks(json: &str) -> Option<Vec<AppEntry>> {
+    let file: BookmarkFile = serde_json::from_str(json).ok()?;
+
+    let mut names: Vec<&String> = file.roots.keys().collect();
+    names.sort_by_key(|name| {
+        let rank = ROOT_ORDER
+            .iter()
+            .position(|r| r == name)
+            .unwrap_or(ROOT_ORDER.len());
+        (rank, name.as_str())
+    });
+
+    let mut entries = Vec::new();
+    let mut folders = Vec::new();
+    for name in names {
+        collect(&file.roots[name], &mut folders, &mut entries);
+    }
+    Some(entries)
+}
+
+fn collect<'a>(node: &'a BookmarkNode, folders: &mut Vec<&'a str>, entries: &mut Vec<AppEntry>) {
+    match node.kind.as_str() {
+        "url" => {
+            let url = node.url.trim();
+            let lower = url.to_ascii_lowercase();
+            if url.is_empty() || SKIPPED_SCHEMES.iter().any(|s| lower.starts_with(s)) {
+                return;
+            }
+            let name = if node.name.trim().is_empty() {
+                url.to_string()
+            } else {
+                node.name.trim().to_string()
+            };
+            entries.push(AppEntry {
+                name,
+                target_path: url.to_string(),
+                kind: EntryKind::Url,
+                url: Some(UrlShortcut {
+                    url: url.to_string(),
+                    ..Default::default()
+                }),
+                context: (!folders.is_empty()).then(|| folders.join("/")),
+                ..Default::default()
+            });
+        }
+        _ => {
+            folders.push(node.name.as_str());
+            for child in &node.children {
+                collect(child, folders, entries);
+            }
+            folders.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+        "checksum": "0123456789abcdef",
+        "roots": {
+            "other": {
+                "children": [
+                    { "id": "10", "name": "Rust", "type": "url", "url": "https://www.rust-lang.org/" }
+                ],
+                "name": "その他のブックマーク",
+                "type": "folder"
+            },
+            "bookmark_bar": {
+                "children": [
+                    { "id": "2", "name": "GitHub", "type": "url", "url": "https://github.com/" },
+                    {
+                        "children": [
+                            { "id": "4", "name": "", "type": "url", "url": "https://docs.rs/" },
+                            { "id": "5", "name": "Bookmarklet", "type": "url", "url": "javascript:alert(1)" }
+                        ],
+                        "id": "3",
+                        "name": "Dev",
+                        "type": "folder"
+                    }
+                ],
+                "name": "ブックマーク バー",
+                "type": "folder"
+            },
+            "synced": { "children": [], "name": "モバイルのブックマーク", "type": "folder" }
+        },
+        "version": 1
+    }"#;
+
+    #[test]
+    fn parses_bookmarks_with_folder_context() {
+        let entries = parse_bookmarks(SAMPLE).expect("parse");
+        let summary: Vec<(&str, &str, Option<&str>)> = entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.target_path.as_str(), e.context.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("GitHub", "https://github.com/", Some("ブックマーク バー")),
+                ("https://docs.rs/", "https://docs.rs/", Some("ブックマーク バー/Dev")),
+                ("Rust", "https://www.rust-lang.org/", Some("その他のブックマーク")),
+            ]
+        );
+        assert!(entries.iter().all(|e| e.launch_target() == e.target_path));
+    }
+
+    #[test]
+    fn invalid_json_is_rejected() {
+        assert!(parse_bookmarks("").is_none());
+        assert!(parse_bookmarks("{\"version\": 1}").is_none());
+    }
+}
@@ -13,6 +13,8 @@ pub struct Config {
     pub paths: PathsConfig,
     #[serde(default)]
     pub search: SearchConfig,
+    #[serde(default)]
+    pub entries: EntriesConfig,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -87,6 +89,10 @@ fn default_show_hidden_system() -> bool {
     false
 }
 
+fn default_rescan_interval_minutes() -> u32 {
+    10
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum SearchModeConfig {
@@ -202,12 +208,129 @@ impl Default for VisualConfig {
     }
 }
 
+fn default_follow_links() -> bool {
+    true
+}
+
+fn default_dedup_targets() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ScanPath {
     pub path: String,
     pub extensions: Vec<String>,
     #[serde(default)]
     pub include_folders: bool,
+    /// gitignore-style patterns relative to `path` (e.g. `node_modules`, `/build`, `*.tmp`).
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Deepest subdirectory level to descend into; 0 scans only `path` itself. None = unlimited.
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+    /// Descend into symbolic links and junctions to directories. Linked folders are still
+    /// listed as entries when disabled; directory cycles are never followed either way.
+    #[serde(default = "default_follow_links")]
+    pub follow_links: bool,
+    /// Entries from this path win when several scan paths hold entries for the same target.
+    /// Otherwise the earlier path in the list wins.
+    #[serde(default)]
+    pub preferred: bool,
+    /// Ranking bonus for entries found under this path; negative values rank them lower.
+    /// Each point counts as much as two launches.
+    #[serde(default)]
+    pub weight: i32,
+}
+
+impl Default for ScanPath {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            extensions: Vec::new(),
+            include_folders: false,
+            exclude: Vec::new(),
+            max_depth: None,
+            follow_links: default_follow_links(),
+            preferred: false,
+            weight: 0,
+        }
+    }
+}
+
+impl ScanPath {
+    /// `path` with environment variables and `~` expanded. This is what gets scanned;
+    /// `path` itself is kept as written so the config stays portable.
+    pub fn expanded_path(&self) -> String {
+        expand_path(&self.path)
+    }
+
+    /// Whether both paths index the same entries, i.e. they differ at most in `weight`,
+    /// which is applied when the cached snapshots are flattened.
+    pub fn scans_same_as(&self, other: &ScanPath) -> bool {
+        *self
+            == ScanPath {
+                weight: self.weight,
+                ..other.clone()
+            }
+    }
+}
+
+/// Expand `%NAME%` environment variables, `$HOME` and a leading `~` in a configured path.
+/// Unknown variables are left as written.
+pub fn expand_path(path: &str) -> String {
+    let home = dirs::home_dir().map(|h| h.to_string_lossy().to_string());
+    expand_path_with(path, &|name| std::env::var(name).ok(), home.as_deref())
+}
+
+fn expand_path_with(
+    path: &str,
+    var: &dyn Fn(&str) -> Option<String>,
+    home: Option<&str>,
+) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut rest = path;
+    if let Some(home) = home
+        && let Some(after) = rest.strip_prefix('~')
+        && (after.is_empty() || after.starts_with(['\\', '/']))
+    {
+        out.push_str(home);
+        rest = after;
+    }
+    while let Some(idx) = rest.find(['%', '$']) {
+        out.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        if let Some(after) = rest.strip_prefix('%')
+            && let Some(end) = after.find('%')
+            && let Some(value) = var(&after[..end]).filter(|_| end > 0)
+        {
+            out.push_str(&value);
+            rest = &after[end + 1..];
+        } else if let Some(after) = rest.strip_prefix("$HOME")
+            && !after.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+            && let Some(home) = var("HOME").or_else(|| home.map(str::to_string))
+        {
+            out.push_str(&home);
+            rest = after;
+        } else {
+            out.push_str(&rest[..1]);
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// A Chromium-format `Bookmarks` file (Chrome, Edge, ...) indexed alongside scan paths.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarkSource {
+    pub path: String,
+}
+
+impl BookmarkSource {
+    /// `path` with environment variables and `~` expanded, like `ScanPath::expanded_path`.
+    pub fn expanded_path(&self) -> String {
+        expand_path(&self.path)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -216,6 +339,68 @@ pub struct PathsConfig {
     pub additional: Vec<String>,
     #[serde(default)]
     pub scan: Vec<ScanPath>,
+    #[serde(default)]
+    pub bookmarks: Vec<BookmarkSource>,
+    /// Steam installation folder (e.g. `C:\Program Files (x86)\Steam`) whose libraries are
+    /// indexed; None or empty disables it. Variables and `~` are expanded as in scan paths.
+    #[serde(default)]
+    pub steam_dir: Option<String>,
+    /// Show shortcuts and files that open the same target (e.g. the Start Menu and Desktop
+    /// shortcuts of one app) as a single entry.
+    #[serde(default = "default_dedup_targets")]
+    pub dedup_targets: bool,
+    /// Watch scan roots and apply changes to the index as they happen.
+    #[serde(default)]
+    pub watch: bool,
+    /// Periodic rescan interval used when a scan root cannot be watched.
+    #[serde(default = "default_rescan_interval_minutes")]
+    pub rescan_interval_minutes: u32,
+}
+
+/// An entry defined in `[[entries.custom]]` rather than found under a scan path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomEntry {
+    pub name: String,
+    /// Program, file or URL to open, e.g. `wt.exe` or `https://example.com/`.
+    pub target: String,
+    /// Command-line arguments passed to `target`.
+    #[serde(default)]
+    pub args: String,
+    /// Working directory; empty uses the launcher's default.
+    #[serde(default)]
+    pub working_dir: String,
+    /// Extra words the entry can be found by besides its name.
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+impl PathsConfig {
+    /// `steam_dir` expanded; None when Steam is not configured.
+    pub fn expanded_steam_dir(&self) -> Option<String> {
+        self.steam_dir
+            .as_deref()
+            .filter(|dir| !dir.trim().is_empty())
+            .map(|dir| expand_path(dir.trim()))
+    }
+}
+
+impl CustomEntry {
+    /// Target followed by its arguments. Identifies the entry in the index and history.
+    pub fn command_line(&self) -> String {
+        let target = self.target.trim();
+        let args = self.args.trim();
+        if args.is_empty() {
+            target.to_string()
+        } else {
+            format!("{} {}", target, args)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntriesConfig {
+    #[serde(default)]
+    pub custom: Vec<CustomEntry>,
 }
 
 impl Default for Config {
@@ -237,8 +422,14 @@ impl Default for Config {
             paths: PathsConfig {
                 additional: Vec::new(),
                 scan: Self::default_scan_paths(),
+                bookmarks: Vec::new(),
+                steam_dir: None,
+                dedup_targets: default_dedup_targets(),
+                watch: false,
+                rescan_interval_minutes: default_rescan_interval_minutes(),
             },
             search: SearchConfig::default(),
+            entries: EntriesConfig::default(),
         }
     }
 }
@@ -258,6 +449,7 @@ impl Config {
                     path: common_start.to_string_lossy().to_string(),
                     extensions: vec![".lnk".to_string()],
                     include_folders: false,
+                    ..Default::default()
                 });
             }
         }
@@ -269,6 +461,7 @@ impl Config {
                     path: desktop.to_string_lossy().to_string(),
                     extensions: vec![".lnk".to_string()],
                     include_folders: false,
+                    ..Default::default()
                 });
             }
 
@@ -310,6 +503,7 @@ impl Config {
                     path,
                     extensions: vec![lnk.clone()],
                     include_folders: false,
+                    ..Default::default()
                 });
             }
         }
@@ -439,6 +633,89 @@ mod tests {
         assert_eq!(config.visual.background_color, "#282828");
     }
 
+    #[test]
+    fn expand_path_replaces_variables_and_home() {
+        let var = |name: &str| match name {
+            "USERPROFILE" => Some("C:\\Users\\taro".to_string()),
+            "APPDATA" => Some("C:\\Users\\taro\\AppData\\Roaming".to_string()),
+            "ProgramData" => Some("C:\\ProgramData".to_string()),
+            _ => None,
+        };
+        let home = Some("/home/taro");
+        let expand = |path: &str| expand_path_with(path, &var, home);
+
+        assert_eq!(expand("%USERPROFILE%\\Desktop"), "C:\\Users\\taro\\Desktop");
+        assert_eq!(
+            expand("%APPDATA%\\Microsoft\\Windows\\Start Menu"),
+            "C:\\Users\\taro\\AppData\\Roaming\\Microsoft\\Windows\\Start Menu"
+        );
+        assert_eq!(expand("%ProgramData%"), "C:\\ProgramData");
+        assert_eq!(expand("~/Apps"), "/home/taro/Apps");
+        assert_eq!(expand("~"), "/home/taro");
+        assert_eq!(expand("$HOME/bin"), "/home/taro/bin");
+
+        // Left as written
+        assert_eq!(expand("%UNKNOWN%\\x"), "%UNKNOWN%\\x");
+        assert_eq!(expand("C:\\100%\\%%"), "C:\\100%\\%%");
+        assert_eq!(expand("~taro/x"), "~taro/x");
+        assert_eq!(expand("$HOMEPATH"), "$HOMEPATH");
+        assert_eq!(expand("\\\\server\\c$\\Tools"), "\\\\server\\c$\\Tools");
+        assert_eq!(expand("D:\\Tools"), "D:\\Tools");
+    }
+
+    #[test]
+    fn scans_same_as_ignores_only_weight() {
+        let base = ScanPath {
+            path: "C:\\Tools".to_string(),
+            ..ScanPath::default()
+        };
+        let weighted = ScanPath {
+            weight: 5,
+            ..base.clone()
+        };
+        assert!(weighted.scans_same_as(&base));
+        let deeper = ScanPath {
+            max_depth: Some(1),
+            ..base.clone()
+        };
+        assert!(!deeper.scans_same_as(&base));
+    }
+
+    #[test]
+    fn deserialize_custom_entries() {
+        let toml_str = r#"
+            [hotkey]
+            modifier = "Alt"
+            key = "Q"
+
+            [appearance]
+            max_results = 8
+            window_width = 600
+
+            [paths]
+
+            [[entries.custom]]
+            name = "PowerShell (管理)"
+            target = "wt.exe"
+            args = "-p PowerShell "
+            working_dir = "C:\\Work"
+            keywords = ["terminal", "pwsh"]
+
+            [[entries.custom]]
+            name = "社内ポータル"
+            target = "https://portal.example.com/"
+        "#;
+        let config: Config = toml::from_str(toml_str).expect("parse");
+        let custom = &config.entries.custom;
+        assert_eq!(custom.len(), 2);
+        assert_eq!(custom[0].command_line(), "wt.exe -p PowerShell");
+        assert_eq!(custom[0].working_dir, "C:\\Work");
+        assert_eq!(custom[0].keywords, vec!["terminal", "pwsh"]);
+        assert_eq!(custom[1].command_line(), "https://portal.example.com/");
+        assert!(custom[1].keywords.is_empty());
+        assert!(Config::default().entries.custom.is_empty());
+    }
+
     #[test]
     fn default_config_has_expected_values() {
         let config = Config::default();
@@ -520,11 +797,65 @@ mod tests {
         let config: Config = toml::from_str(toml_str).expect("parse");
         assert_eq!(config.paths.additional, vec!["C:\\Old"]);
         assert!(config.paths.scan.is_empty());
+        assert!(!config.paths.watch);
+        assert_eq!(config.paths.rescan_interval_minutes, 10);
         assert!(config.appearance.show_icons);
         assert!(config.general.hotkey_toggle);
         assert_eq!(config.visual.preset, ThemePreset::Obsidian);
     }
 
+    #[test]
+    fn deserialize_watch_settings() {
+        let toml_str = r#"
+            [hotkey]
+            modifier = "Alt"
+            key = "Q"
+
+            [appearance]
+            max_results = 8
+            window_width = 600
+
+            [paths]
+            watch = true
+            rescan_interval_minutes = 30
+        "#;
+        let config: Config = toml::from_str(toml_str).expect("parse");
+        assert!(config.paths.watch);
+        assert_eq!(config.paths.rescan_interval_minutes, 30);
+    }
+
+    #[test]
+    fn deserialize_scan_path_options() {
+        let toml_str = r#"
+            [hotkey]
+            modifier = "Alt"
+            key = "Q"
+
+            [appearance]
+            max_results = 8
+            window_width = 600
+
+            [[paths.scan]]
+            path = "C:\\Dev"
+            extensions = [".exe"]
+            exclude = ["node_modules", "/target"]
+            max_depth = 3
+
+            [[paths.scan]]
+            path = "C:\\Tools"
+            extensions = [".exe"]
+            follow_links = false
+        "#;
+        let config: Config = toml::from_str(toml_str).expect("parse");
+        let scan = &config.paths.scan;
+        assert_eq!(scan[0].exclude, vec!["node_modules", "/target"]);
+        assert_eq!(scan[0].max_depth, Some(3));
+        assert!(scan[1].exclude.is_empty());
+        assert_eq!(scan[1].max_depth, None);
+        assert!(scan[0].follow_links);
+        assert!(!scan[1].follow_links);
+    }
+
     #[test]
     fn alt_space_is_rewritten_to_alt_q() {
         let toml_str = r#"
@@ -635,6 +966,7 @@ mod tests {
             path: "C:\\Tools".to_string(),
             extensions: vec![".exe".to_string()],
             include_folders: false,
+            ..Default::default()
         }];
         config.paths.additional = vec!["C:\\Tools".to_string(), "D:\\New".to_string()];
 
@@ -656,6 +988,7 @@ mod tests {
             path: "C:\\TOOLS".to_string(),
             extensions: vec![".exe".to_string()],
             include_folders: false,
+            ..Default::default()
         }];
         config.paths.additional = vec!["c:\\tools".to_string()];
 
@@ -673,6 +1006,7 @@ mod tests {
             path: "C:\\Links".to_string(),
             extensions: vec![".lnk".to_string()],
             include_folders: false,
+            ..Default::default()
         }];
         config.paths.additional = vec!["C:\\Links".to_string()];
 
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::binfmt::{deserialize_with_header, serialize_with_header};
+use crate::config::Config;
+
+const DIAGNOSTICS_MAGIC: [u8; 4] = *b"SCNR";
+const DIAGNOSTICS_VERSION: u32 = 1;
+
+/// A directory that could not be listed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanError {
+    pub path: String,
+    pub message: String,
+    pub permission_denied: bool,
+}
+
+impl ScanError {
+    pub fn new(path: String, error: &std::io::Error) -> Self {
+        Self {
+            path,
+            message: error.to_string(),
+            permission_denied: error.kind() == std::io::ErrorKind::PermissionDenied,
+        }
+    }
+}
+
+/// What a walk of one `ScanPath` found. Directories reused from the cache count as visited.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanPathReport {
+    pub path: String,
+    /// Entries under this path before deduplication against other scan paths.
+    pub entries: usize,
+    pub dirs_visited: usize,
+    /// Hidden/system items left out by the visibility policy.
+    pub hidden_skipped: usize,
+    pub errors: Vec<ScanError>,
+    pub elapsed_ms: u64,
+}
+
+/// Report of the last full or incremental scan, saved next to `index.bin`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanDiagnostics {
+    /// Seconds since the epoch.
+    pub scanned_at: u64,
+    pub paths: Vec<ScanPathReport>,
+}
+
+impl ScanDiagnostics {
+    pub fn new(paths: Vec<ScanPathReport>) -> Self {
+        Self {
+            scanned_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            paths,
+        }
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    Config::config_dir().map(|p| p.join("scan_report.bin"))
+}
+
+pub fn load_diagnostics() -> Option<ScanDiagnostics> {
+    let bytes = std::fs::read(path()?).ok()?;
+    deserialize_with_header(&bytes, DIAGNOSTICS_MAGIC, DIAGNOSTICS_VERSION)
+}
+
+pub fn save_diagnostics(diagnostics: &ScanDiagnostics) {
+    let Some(path) = path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let Some(bytes) = serialize_with_header(DIAGNOSTICS_MAGIC, DIAGNOSTICS_VERSION, diagnostics)
+    else {
+        return;
+    };
+    let tmp_path = path.with_extension("bin.tmp");
+    if std::fs::write(&tmp_path, &bytes).is_ok() {
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::rename(&tmp_path, &path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostics_roundtrip() {
+        let diagnostics = ScanDiagnostics {
+            scanned_at: 1700000000,
+            paths: vec![ScanPathReport {
+                path: "C:\\Menu".to_string(),
+                entries: 12,
+                dirs_visited: 3,
+                hidden_skipped: 1,
+                errors: vec![ScanError::new(
+                    "C:\\Menu\\Locked".to_string(),
+                    &std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+                )],
+                elapsed_ms: 7,
+            }],
+        };
+        let bytes = serialize_with_header(DIAGNOSTICS_MAGIC, DIAGNOSTICS_VERSION, &diagnostics)
+            .expect("serialize");
+        let restored: ScanDiagnostics =
+            deserialize_with_header(&bytes, DIAGNOSTICS_MAGIC, DIAGNOSTICS_VERSION)
+                .expect("deserialize");
+        assert_eq!(restored, diagnostics);
+        assert!(restored.paths[0].errors[0].permission_denied);
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, PathsConfig};
+use crate::history::{HistoryImportMode, HistoryStore};
+use crate::indexer::{self, AppEntry};
+use crate::window_data;
+
+pub const INDEX_FILE: &str = "index.json";
+pub const HISTORY_FILE: &str = "history.json";
+pub const WINDOW_FILE: &str = "window.json";
+
+/// Why a JSON file could not be imported. Nothing is written in that case.
+#[derive(Debug)]
+pub enum ImportError {
+    Read(std::io::Error),
+    Parse(serde_json::Error),
+    /// Well-formed JSON whose contents cannot be used, e.g. an index built for other settings.
+    Invalid(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Read(e) => write!(f, "読み込めません: {}", e),
+            ImportError::Parse(e) => write!(f, "JSON として解釈できません: {}", e),
+            ImportError::Invalid(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(e: serde_json::Error) -> Self {
+        ImportError::Parse(e)
+    }
+}
+
+/// Folder the slash commands export to and import from.
+pub fn export_dir() -> Option<PathBuf> {
+    Config::config_dir().map(|p| p.join("export"))
+}
+
+/// Write every store that exists as pretty-printed JSON into `dir`.
+/// Returns the files written.
+pub fn export_all(dir: &Path, history: &HistoryStore) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let stores = [
+        (INDEX_FILE, indexer::export_index_json()),
+        (HISTORY_FILE, history.export_json()),
+        (WINDOW_FILE, window_data::export_json()),
+    ];
+    let mut written = Vec::new();
+    for (name, json) in stores {
+        let Some(json) = json else {
+            continue;
+        };
+        let path = dir.join(name);
+        std::fs::write(&path, json)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Result of `import_all` for each store; None when its file was not in the folder.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// The imported index entries, to be swapped into the search engine.
+    pub index: Option<Result<Vec<AppEntry>, ImportError>>,
+    /// Number of launch history entries after the import.
+    pub history: Option<Result<usize, ImportError>>,
+    pub window: Option<Result<(), ImportError>>,
+}
+
+/// Import every export file found in `dir`. Each store is validated and written on its own,
+/// so a broken file does not keep the others from being imported.
+pub fn import_all(
+    dir: &Path,
+    paths: &PathsConfig,
+    show_hidden_system: bool,
+    history: &mut HistoryStore,
+    mode: HistoryImportMode,
+) -> ImportReport {
+    ImportReport {
+        index: read_if_present(&dir.join(INDEX_FILE)).map(|json| {
+            json.and_then(|json| indexer::import_index_json(&json, paths, show_hidden_system))
+        }),
+        history: read_if_present(&dir.join(HISTORY_FILE))
+            .map(|json| json.and_then(|json| history.import_json(&json, mode))),
+        window: read_if_present(&dir.join(WINDOW_FILE))
+            .map(|json| json.and_then(|json| window_data::import_json(&json))),
+    }
+}
+
+fn read_if_present(path: &Path) -> Option<Result<String, ImportError>> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => Some(Ok(json)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => Some(Err(ImportError::Read(e))),
+    }
+}
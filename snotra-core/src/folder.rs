@@ -1,12 +1,12 @@
 use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
-use std::os::windows::fs::MetadataExt;
 use std::path::{Path, PathBuf};
-use windows::Win32::Storage::FileSystem::{FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_SYSTEM};
 
 use crate::history::HistoryStore;
-use crate::search::SearchMode;
+use crate::indexer::EntryKind;
+use crate::query::{fold_text, normalize_query};
+use crate::search::{self, SearchMode};
 use crate::ui_types::SearchResult;
+use crate::visibility::{self, DirVisibility};
 
 pub fn list_folder(
     dir: &Path,
@@ -22,30 +22,38 @@ pub fn list_folder(
             path: dir.to_string_lossy().to_string(),
             is_folder: false,
             is_error: true,
+            description: None,
+            kind: EntryKind::Folder,
+            highlights: Vec::new(),
         }];
     };
 
     let matcher = SkimMatcherV2::default();
+    let filter = normalize_query(filter);
+    let visibility = DirVisibility::new(visibility::policy_for(show_hidden_system), dir);
 
     let mut entries: Vec<SearchResult> = read_dir
         .flatten()
         .filter_map(|entry| {
             let path = entry.path();
-            if !show_hidden_system && !is_visible_entry(&path) {
+            if !visibility.is_visible(&path) {
                 return None;
             }
             let name = entry.file_name().to_string_lossy().to_string();
 
-            if !filter.is_empty() && !matches_filter(&name, filter, mode, &matcher) {
+            if !filter.is_empty() && !matches_filter(&name, &filter, mode, &matcher) {
                 return None;
             }
 
             let is_folder = path.is_dir();
             Some(SearchResult {
+                kind: EntryKind::for_path(&name, is_folder),
                 name,
                 path: path.to_string_lossy().to_string(),
                 is_folder,
                 is_error: false,
+                description: None,
+                highlights: Vec::new(),
             })
         })
         .collect();
@@ -81,39 +89,31 @@ pub fn list_folder(
         .into_iter()
         .map(|(_, _, i)| {
             // Take ownership by swapping with a dummy to avoid clone
-            std::mem::replace(
+            let mut result = std::mem::replace(
                 &mut entries[i],
                 SearchResult {
                     name: String::new(),
                     path: String::new(),
                     is_folder: false,
                     is_error: false,
+                    description: None,
+                    kind: EntryKind::Document,
+                    highlights: Vec::new(),
                 },
-            )
+            );
+            if !filter.is_empty() {
+                result.highlights =
+                    search::highlight_indices(mode, &matcher, &result.name, &filter);
+            }
+            result
         })
         .collect()
 }
 
+/// `filter` is a normalized query; its tokens may match the name's words in any order.
 fn matches_filter(name: &str, filter: &str, mode: SearchMode, matcher: &SkimMatcherV2) -> bool {
-    let name_lower = name.to_lowercase();
-    let filter_lower = filter.to_lowercase();
-    match mode {
-        SearchMode::Prefix => name_lower.starts_with(&filter_lower),
-        SearchMode::Substring => name_lower.contains(&filter_lower),
-        SearchMode::Fuzzy => matcher
-            .fuzzy_match(&name_lower, &filter_lower)
-            .is_some(),
-    }
-}
-
-fn is_visible_entry(path: &Path) -> bool {
-    let Ok(meta) = std::fs::metadata(path) else {
-        return true;
-    };
-    let attrs = meta.file_attributes();
-    let hidden = (attrs & FILE_ATTRIBUTE_HIDDEN.0) != 0;
-    let system = (attrs & FILE_ATTRIBUTE_SYSTEM.0) != 0;
-    !hidden && !system
+    let words = search::split_words(name);
+    search::match_score(mode, matcher, &fold_text(name), &words, filter).is_some()
 }
 
 pub fn parent_for_navigation(current_dir: &str) -> Option<PathBuf> {
@@ -214,6 +214,21 @@ mod tests {
         let _ = fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn list_folder_filter_matches_tokens_in_any_order() {
+        let dir = temp_dir_with_contents("filter_tokens");
+        fs::write(dir.join("Annual Report 2024.xlsx"), "").unwrap();
+        fs::write(dir.join("Report Draft.docx"), "").unwrap();
+
+        for mode in [SearchMode::Prefix, SearchMode::Substring, SearchMode::Fuzzy] {
+            let results = list_folder(&dir, "2024  report", mode, true, &empty_history(), 100);
+            assert_eq!(results.len(), 1, "{:?}", mode);
+            assert_eq!(results[0].name, "Annual Report 2024.xlsx");
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn list_folder_filter_is_case_insensitive() {
         let dir = temp_dir_with_contents("filter_case");
@@ -291,6 +306,32 @@ mod tests {
         let _ = fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn filter_folds_width_and_kana() {
+        let dir = temp_dir_with_contents("folded_filter");
+        fs::write(dir.join("メモ.txt"), "").unwrap();
+
+        let results = list_folder(&dir, "ﾒﾓ", SearchMode::Prefix, true, &empty_history(), 100);
+        assert_eq!(results.len(), 1);
+        let results = list_folder(&dir, "めも", SearchMode::Prefix, true, &empty_history(), 100);
+        assert_eq!(results.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn filtered_results_carry_highlights() {
+        let dir = temp_dir_with_contents("filter_highlights");
+        fs::write(dir.join("Report.txt"), "").unwrap();
+
+        let results = list_folder(&dir, "port", SearchMode::Substring, true, &empty_history(), 100);
+        assert_eq!(results[0].highlights, vec![2, 3, 4, 5]);
+        let results = list_folder(&dir, "", SearchMode::Substring, true, &empty_history(), 100);
+        assert!(results[0].highlights.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn fuzzy_mode_matches_skipped_characters() {
         let dir = temp_dir_with_contents("fuzzy_filter");
@@ -308,9 +349,10 @@ mod tests {
         let dir = temp_dir_with_contents("substring_not_fuzzy");
         fs::write(dir.join("Visual Studio Code.txt"), "").unwrap();
 
+        // "vsd" is neither a substring nor spelled by word starts.
         let results = list_folder(
             &dir,
-            "vsc",
+            "vsd",
             SearchMode::Substring,
             true,
             &empty_history(),
@@ -321,6 +363,23 @@ mod tests {
         let _ = fs::remove_dir_all(&dir);
     }
 
+    #[cfg(not(windows))]
+    #[test]
+    fn list_folder_hides_dotfiles_unless_shown() {
+        let dir = temp_dir_with_contents("dotfiles");
+        fs::write(dir.join(".profile"), "").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let results = list_folder(&dir, "", SearchMode::Substring, false, &empty_history(), 100);
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["notes.txt"]);
+
+        let results = list_folder(&dir, "", SearchMode::Substring, true, &empty_history(), 100);
+        assert_eq!(results.len(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn detects_drive_root() {
         assert!(is_navigation_root("C:\\"));
@@ -6,10 +6,13 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::binfmt::{deserialize_with_header, serialize_with_header};
 use crate::config::Config;
+use crate::export::ImportError;
 use crate::query::normalize_query;
 
 const HISTORY_MAGIC: [u8; 4] = *b"HIST";
-const HISTORY_VERSION: u32 = 1;
+const HISTORY_VERSION: u32 = 2;
+/// Version whose query keys predate width/kana/diacritic folding in `normalize_query`.
+const LEGACY_HISTORY_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GlobalEntry {
@@ -25,6 +28,97 @@ pub struct HistoryData {
     pub folder_expansion: HashMap<String, u32>,
 }
 
+impl HistoryData {
+    /// Add `other`'s counts to this history; the later `last_launched` wins.
+    fn merge(&mut self, other: HistoryData) {
+        for (path, entry) in other.global {
+            let current = self.global.entry(path).or_default();
+            current.launch_count = current.launch_count.saturating_add(entry.launch_count);
+            current.last_launched = current.last_launched.max(entry.last_launched);
+        }
+        for (query, paths) in other.query {
+            let current = self.query.entry(query).or_default();
+            for (path, count) in paths {
+                let c = current.entry(path).or_insert(0);
+                *c = c.saturating_add(count);
+            }
+        }
+        for (path, count) in other.folder_expansion {
+            let c = self.folder_expansion.entry(path).or_insert(0);
+            *c = c.saturating_add(count);
+        }
+    }
+}
+
+/// How `HistoryStore::import_json` combines imported history with the current one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryImportMode {
+    /// Add imported counts to the current ones.
+    Merge,
+    /// Discard the current history.
+    Replace,
+}
+
+/// Parse an exported history, rejecting empty paths and re-normalizing query keys
+/// (files edited by hand may use other spellings of the same query).
+fn parse_history_json(json: &str) -> Result<HistoryData, ImportError> {
+    let raw: HistoryData = serde_json::from_str(json)?;
+    let has_empty_path = raw.global.keys().any(|p| p.trim().is_empty())
+        || raw.folder_expansion.keys().any(|p| p.trim().is_empty())
+        || raw
+            .query
+            .values()
+            .any(|paths| paths.keys().any(|p| p.trim().is_empty()));
+    if has_empty_path {
+        return Err(ImportError::Invalid(
+            "空のパスを含む履歴は取り込めません".to_string(),
+        ));
+    }
+
+    let mut data = HistoryData {
+        global: raw.global,
+        query: HashMap::new(),
+        folder_expansion: raw.folder_expansion,
+    };
+    data.merge(HistoryData {
+        query: normalize_query_keys(raw.query),
+        ..Default::default()
+    });
+    Ok(data)
+}
+
+/// Re-normalize query keys, summing the counts of keys that become equal and dropping
+/// keys that normalize to nothing.
+fn normalize_query_keys(
+    query: HashMap<String, HashMap<String, u32>>,
+) -> HashMap<String, HashMap<String, u32>> {
+    let mut merged: HashMap<String, HashMap<String, u32>> = HashMap::new();
+    for (query, paths) in query {
+        let query = normalize_query(&query);
+        if query.is_empty() {
+            continue;
+        }
+        let current = merged.entry(query).or_default();
+        for (path, count) in paths {
+            let c = current.entry(path).or_insert(0);
+            *c = c.saturating_add(count);
+        }
+    }
+    merged
+}
+
+/// Decode `history.bin`; query keys of the legacy version are migrated to the current
+/// normalization. The flag reports whether the file should be rewritten.
+fn decode_history(bytes: &[u8]) -> Option<(HistoryData, bool)> {
+    if let Some(data) = deserialize_with_header(bytes, HISTORY_MAGIC, HISTORY_VERSION) {
+        return Some((data, false));
+    }
+    let mut data: HistoryData =
+        deserialize_with_header(bytes, HISTORY_MAGIC, LEGACY_HISTORY_VERSION)?;
+    data.query = normalize_query_keys(std::mem::take(&mut data.query));
+    Some((data, true))
+}
+
 pub struct HistoryStore {
     data: HistoryData,
     top_n: usize,
@@ -34,15 +128,15 @@ pub struct HistoryStore {
 
 impl HistoryStore {
     pub fn load(top_n: usize, max_history_display: usize) -> Self {
-        let mut decode_failed = false;
+        let mut needs_save = false;
         let data = if let Some(path) = Self::data_path() {
-            match fs::read(&path)
-                .ok()
-                .and_then(|bytes| deserialize_with_header(&bytes, HISTORY_MAGIC, HISTORY_VERSION))
-            {
-                Some(data) => data,
+            match fs::read(&path).ok().and_then(|bytes| decode_history(&bytes)) {
+                Some((data, migrated)) => {
+                    needs_save = migrated;
+                    data
+                }
                 None => {
-                    decode_failed = true;
+                    needs_save = true;
                     HistoryData::default()
                 }
             }
@@ -56,12 +150,22 @@ impl HistoryStore {
             max_history_display,
             dirty_count: 0,
         };
-        if decode_failed {
+        if needs_save {
             store.save();
         }
         store
     }
 
+    /// An empty store that is not loaded from `history.bin`.
+    pub fn in_memory(top_n: usize, max_history_display: usize) -> Self {
+        Self {
+            data: HistoryData::default(),
+            top_n,
+            max_history_display,
+            dirty_count: 0,
+        }
+    }
+
     pub fn save(&mut self) {
         self.prune();
 
@@ -134,6 +238,11 @@ impl HistoryStore {
             .unwrap_or(0)
     }
 
+    /// Launch counts per path for an already normalized query.
+    pub fn query_counts(&self, norm_query: &str) -> Option<&HashMap<String, u32>> {
+        self.data.query.get(norm_query)
+    }
+
     pub fn last_launched(&self, path: &str) -> Option<u64> {
         self.data.global.get(path).map(|e| e.last_launched)
     }
@@ -146,7 +255,7 @@ impl HistoryStore {
             .map(|(path, entry)| (path.as_str(), entry.last_launched))
             .collect();
 
-        entries.sort_by(|a, b| b.1.cmp(&a.1));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.1));
         entries.truncate(self.max_history_display);
         entries.into_iter().map(|(path, _)| path).collect()
     }
@@ -168,6 +277,27 @@ impl HistoryStore {
             .unwrap_or(0)
     }
 
+    pub fn export_json(&self) -> Option<String> {
+        serde_json::to_string_pretty(&self.data).ok()
+    }
+
+    /// Import an `export_json` document and save. Returns the number of launched entries.
+    /// The current history is left untouched when the document is invalid.
+    pub fn import_json(
+        &mut self,
+        json: &str,
+        mode: HistoryImportMode,
+    ) -> Result<usize, ImportError> {
+        let imported = parse_history_json(json)?;
+        match mode {
+            HistoryImportMode::Merge => self.data.merge(imported),
+            HistoryImportMode::Replace => self.data = imported,
+        }
+        self.save();
+        self.dirty_count = 0;
+        Ok(self.data.global.len())
+    }
+
     fn data_path() -> Option<PathBuf> {
         Config::config_dir().map(|p| p.join("history.bin"))
     }
@@ -176,7 +306,7 @@ impl HistoryStore {
         // Prune global + query entries
         if self.data.global.len() > self.top_n {
             let mut entries: Vec<_> = self.data.global.drain().collect();
-            entries.sort_by(|a, b| b.1.launch_count.cmp(&a.1.launch_count));
+            entries.sort_by_key(|e| std::cmp::Reverse(e.1.launch_count));
             entries.truncate(self.top_n);
 
             let surviving: HashMap<String, GlobalEntry> = entries.into_iter().collect();
@@ -192,7 +322,7 @@ impl HistoryStore {
         // Prune folder_expansion independently
         if self.data.folder_expansion.len() > self.top_n {
             let mut fentries: Vec<_> = self.data.folder_expansion.drain().collect();
-            fentries.sort_by(|a, b| b.1.cmp(&a.1));
+            fentries.sort_by_key(|e| std::cmp::Reverse(e.1));
             fentries.truncate(self.top_n);
             self.data.folder_expansion = fentries.into_iter().collect();
         }
@@ -399,6 +529,45 @@ mod tests {
         assert_eq!(roundtripped.folder_expansion["C:\\Projects"], 2);
     }
 
+    #[test]
+    fn legacy_history_query_keys_are_migrated() {
+        let mut data = HistoryData::default();
+        data.query
+            .entry("ﾒﾓ帳".to_string())
+            .or_default()
+            .insert("C:\\memo.lnk".to_string(), 2);
+        data.query
+            .entry("メモ帳".to_string())
+            .or_default()
+            .insert("C:\\memo.lnk".to_string(), 3);
+        data.query
+            .entry("ｆｉｒｅｆｏｘ".to_string())
+            .or_default()
+            .insert("C:\\firefox.lnk".to_string(), 1);
+        let bytes = serialize_with_header(HISTORY_MAGIC, LEGACY_HISTORY_VERSION, &data)
+            .expect("serialize");
+
+        let (migrated, needs_save) = decode_history(&bytes).expect("decode");
+        assert!(needs_save);
+        assert_eq!(migrated.query.len(), 2);
+        assert_eq!(migrated.query[&normalize_query("メモ帳")]["C:\\memo.lnk"], 5);
+        assert_eq!(migrated.query["firefox"]["C:\\firefox.lnk"], 1);
+
+        let bytes =
+            serialize_with_header(HISTORY_MAGIC, HISTORY_VERSION, &migrated).expect("serialize");
+        let (current, needs_save) = decode_history(&bytes).expect("decode");
+        assert!(!needs_save);
+        assert_eq!(current.query.len(), 2);
+    }
+
+    #[test]
+    fn query_count_folds_width_and_kana() {
+        let mut store = fresh_store();
+        store.record_launch("C:\\memo.lnk", "ﾒﾓ");
+        assert_eq!(store.query_count("めも", "C:\\memo.lnk"), 1);
+        assert_eq!(store.query_count("メモ", "C:\\memo.lnk"), 1);
+    }
+
     #[test]
     fn prune_keeps_top_n_by_launch_count() {
         let mut store = fresh_store_with_top_n(2);
@@ -432,4 +601,62 @@ mod tests {
         assert!(store.data.global.contains_key("C:\\med.lnk"));
         assert!(!store.data.global.contains_key("C:\\low.lnk"));
     }
+
+    #[test]
+    fn history_json_import_merges_and_normalizes() {
+        let mut store = fresh_store();
+        store.data.global.insert(
+            "C:\\app.lnk".to_string(),
+            GlobalEntry {
+                launch_count: 2,
+                last_launched: 300,
+            },
+        );
+        store
+            .data
+            .query
+            .entry("note".to_string())
+            .or_default()
+            .insert("C:\\app.lnk".to_string(), 1);
+        let json = store.export_json().expect("export");
+        assert_eq!(parse_history_json(&json).expect("valid").global.len(), 1);
+
+        let imported = parse_history_json(
+            r#"{
+                "global": {
+                    "C:\\app.lnk": { "launch_count": 3, "last_launched": 100 },
+                    "C:\\other.lnk": { "launch_count": 1, "last_launched": 500 }
+                },
+                "query": {
+                    "  Note ": { "C:\\app.lnk": 2 },
+                    "note": { "C:\\app.lnk": 1 }
+                }
+            }"#,
+        )
+        .expect("valid");
+        assert_eq!(imported.query["note"]["C:\\app.lnk"], 3);
+        assert!(imported.folder_expansion.is_empty());
+
+        store.data.merge(imported);
+        assert_eq!(store.global_count("C:\\app.lnk"), 5);
+        assert_eq!(store.last_launched("C:\\app.lnk"), Some(300));
+        assert_eq!(store.global_count("C:\\other.lnk"), 1);
+        assert_eq!(store.query_count("note", "C:\\app.lnk"), 4);
+    }
+
+    #[test]
+    fn history_json_import_rejects_invalid_documents() {
+        assert!(matches!(
+            parse_history_json("[]"),
+            Err(ImportError::Parse(_))
+        ));
+        let empty_path = r#"{
+            "global": { " ": { "launch_count": 1, "last_launched": 1 } },
+            "query": {}
+        }"#;
+        assert!(matches!(
+            parse_history_json(empty_path),
+            Err(ImportError::Invalid(_))
+        ));
+    }
 }
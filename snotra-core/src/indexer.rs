@@ -1,406 +1,2865 @@
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
-use std::os::windows::fs::MetadataExt;
+use std::cell::Cell;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::thread;
-use std::time::{SystemTime, UNIX_EPOCH};
-use windows::Win32::Storage::FileSystem::{FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_SYSTEM};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-use crate::binfmt::{deserialize_with_header, serialize_with_header};
-use crate::config::{Config, ScanPath};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::binfmt::{self, deserialize_with_header};
+use crate::bookmarks;
+use crate::config::{self, BookmarkSource, Config, CustomEntry, PathsConfig, ScanPath};
+use crate::diagnostics::{self, ScanDiagnostics, ScanError, ScanPathReport};
+use crate::export::ImportError;
+use crate::shortcut::{self, ShortcutInfo, UrlShortcut};
+use crate::steam;
+use crate::version_info;
+use crate::visibility::{self, DirVisibility, VisibilityPolicy};
 
 const INDEX_MAGIC: [u8; 4] = *b"INDX";
-const INDEX_CACHE_VERSION: u32 = 1;
+const INDEX_CACHE_VERSION: u32 = 2;
+/// Flat entry list without directory snapshots, written by the first releases.
+const INDEX_CACHE_VERSION_V1: u32 = 1;
+
+/// Held across every load-modify-save of `index.bin`, so the startup rescan, the watcher,
+/// rebuilds and imports never overwrite each other's results.
+static CACHE_LOCK: Mutex<()> = Mutex::new(());
+/// Makes temporary file names unique per write.
+static CACHE_WRITES: AtomicU64 = AtomicU64::new(0);
+
+const EXECUTABLE_EXTENSIONS: [&str; 6] = [".exe", ".com", ".msi", ".msc", ".cpl", ".scr"];
+const COMMAND_EXTENSIONS: [&str; 5] = [".bat", ".cmd", ".ps1", ".vbs", ".wsf"];
+const SHORTCUT_EXTENSIONS: [&str; 2] = [".lnk", ".appref-ms"];
+
+/// What an entry is, for labelling and filtering in the UI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// `.lnk` and other application shortcuts.
+    AppShortcut,
+    Executable,
+    #[default]
+    Document,
+    Folder,
+    Url,
+    /// Scripts and command lines.
+    Command,
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl EntryKind {
+    /// Classify a file-system entry by its extension.
+    pub fn for_path(path: &str, is_folder: bool) -> Self {
+        if is_folder {
+            return Self::Folder;
+        }
+        let name = path.rsplit(['\\', '/']).next().unwrap_or(path);
+        let ext = match name.rfind('.') {
+            Some(idx) => name[idx..].to_lowercase(),
+            None => return Self::Document,
+        };
+        let ext = ext.as_str();
+        if SHORTCUT_EXTENSIONS.contains(&ext) {
+            Self::AppShortcut
+        } else if ext == ".url" {
+            Self::Url
+        } else if EXECUTABLE_EXTENSIONS.contains(&ext) {
+            Self::Executable
+        } else if COMMAND_EXTENSIONS.contains(&ext) {
+            Self::Command
+        } else {
+            Self::Document
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppEntry {
     pub name: String,
     pub target_path: String,
+    /// Same as `kind == EntryKind::Folder`.
     pub is_folder: bool,
+    pub kind: EntryKind,
+    /// File size in bytes at scan time; None for folders and non-file entries.
+    pub size: Option<u64>,
+    /// Last modification time (seconds since the epoch) at scan time; None for non-file entries.
+    pub modified: Option<u64>,
+    /// Paths of entries for the same target that were collapsed into this one.
+    /// History recorded under them counts for this entry.
+    pub aliases: Vec<String>,
+    /// Parsed contents when the entry is a `.lnk` shortcut.
+    pub shortcut: Option<ShortcutInfo>,
+    /// Set for URL entries (e.g. `.url` files); they are opened as a URL instead of a path.
+    pub url: Option<UrlShortcut>,
+    /// Where a non-file entry lives, e.g. a bookmark's folder path (`ブックマーク バー/Dev`).
+    pub context: Option<String>,
+    /// Extra words the entry can be found by besides its name: custom entry keywords, or
+    /// the product name, description and company from an executable's version resource.
+    pub keywords: Vec<String>,
+    /// `weight` of the scan path the entry was found under; 0 for other sources.
+    pub weight: i32,
+    /// Defined in `[[entries.custom]]`. Launched from `shortcut`'s target, arguments and
+    /// working directory; `target_path` holds the command line.
+    pub custom: bool,
+}
+
+impl AppEntry {
+    /// What to hand to the shell when launching: the URL for URL entries, else the path.
+    pub fn launch_target(&self) -> &str {
+        match &self.url {
+            Some(url) => &url.url,
+            None => &self.target_path,
+        }
+    }
 }
 
-pub fn scan_all(scan_paths: &[ScanPath], show_hidden_system: bool) -> Vec<AppEntry> {
-    let mut entries = Vec::new();
-    let mut seen = std::collections::HashSet::new();
+/// Entries for the `[[entries.custom]]` section. Entries without a name or target are
+/// skipped, as are later entries with the same command line.
+pub fn custom_entries(custom: &[CustomEntry]) -> Vec<AppEntry> {
+    let mut seen = HashSet::new();
+    custom
+        .iter()
+        .filter(|c| !c.name.trim().is_empty() && !c.target.trim().is_empty())
+        .filter(|c| seen.insert(normalize_entry_key(&c.command_line())))
+        .map(custom_entry)
+        .collect()
+}
 
-    for sp in scan_paths {
-        let ext_set: HashSet<String> = sp.extensions.iter().map(|e| e.to_lowercase()).collect();
-        scan_directory_with_extensions(
-            Path::new(&sp.path),
-            &ext_set,
-            sp.include_folders,
-            show_hidden_system,
-            &mut entries,
-            &mut seen,
-        );
+fn custom_entry(custom: &CustomEntry) -> AppEntry {
+    let target = custom.target.trim();
+    let non_empty = |s: &str| (!s.trim().is_empty()).then(|| s.trim().to_string());
+    let url = is_url(target).then(|| UrlShortcut {
+        url: target.to_string(),
+        ..Default::default()
+    });
+    AppEntry {
+        name: custom.name.trim().to_string(),
+        target_path: custom.command_line(),
+        kind: if url.is_some() {
+            EntryKind::Url
+        } else {
+            EntryKind::for_path(target, false)
+        },
+        shortcut: Some(ShortcutInfo {
+            target: Some(target.to_string()),
+            arguments: non_empty(&custom.args),
+            working_dir: non_empty(&custom.working_dir),
+            ..Default::default()
+        }),
+        url,
+        keywords: custom
+            .keywords
+            .iter()
+            .filter_map(|k| non_empty(k))
+            .collect(),
+        custom: true,
+        ..Default::default()
     }
+}
 
-    entries
+/// `scheme:...` with a scheme of two or more characters, so drive letters do not count.
+fn is_url(target: &str) -> bool {
+    target.find(':').is_some_and(|idx| {
+        idx > 1
+            && target[..idx]
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
 }
 
-/// Recursively scan for files matching given extensions, optionally including folders
-fn scan_directory_with_extensions(
-    dir: &Path,
-    extensions: &HashSet<String>,
+/// Replace the custom entries in `entries` with those of `custom`. Custom entries come first;
+/// scanned entries whose path equals a custom command line are dropped in their favour.
+pub fn merge_custom_entries(entries: Vec<AppEntry>, custom: &[CustomEntry]) -> Vec<AppEntry> {
+    let mut merged = custom_entries(custom);
+    let keys: HashSet<String> = merged
+        .iter()
+        .map(|e| normalize_entry_key(&e.target_path))
+        .collect();
+    merged.extend(
+        entries
+            .into_iter()
+            .filter(|e| !e.custom && !keys.contains(&normalize_entry_key(&e.target_path))),
+    );
+    merged
+}
+
+/// Full scan of `scan_paths`, collapsing entries with the same launch target when
+/// `dedup_targets` is on. Returns None when `cancel` was triggered.
+pub fn scan_all(
+    scan_paths: &[ScanPath],
+    show_hidden_system: bool,
+    dedup_targets: bool,
+    on_progress: &dyn Fn(&ScanProgress),
+    cancel: &CancelToken,
+) -> Option<Vec<AppEntry>> {
+    let monitor = ScanMonitor::new(on_progress, Some(cancel));
+    let (roots, _) = scan_directories(scan_paths, show_hidden_system, None, &monitor);
+    (!cancel.is_cancelled()).then(|| flatten_snapshots(scan_paths, &roots, dedup_targets))
+}
+
+/// Running totals of a scan, reported after each directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanProgress {
+    pub dirs_visited: usize,
+    pub entries: usize,
+    /// Directory that was just listed or reused.
+    pub current_path: String,
+}
+
+/// Stops a running scan at the next directory. Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Progress sink and cancellation shared by every scan path of one scan.
+struct ScanMonitor<'a> {
+    on_progress: &'a dyn Fn(&ScanProgress),
+    cancel: Option<&'a CancelToken>,
+    dirs_visited: Cell<usize>,
+    entries: Cell<usize>,
+}
+
+impl<'a> ScanMonitor<'a> {
+    fn new(on_progress: &'a dyn Fn(&ScanProgress), cancel: Option<&'a CancelToken>) -> Self {
+        Self {
+            on_progress,
+            cancel,
+            dirs_visited: Cell::new(0),
+            entries: Cell::new(0),
+        }
+    }
+
+    /// For scans nobody watches or stops (background rescans, watcher updates).
+    fn silent() -> Self {
+        Self::new(&|_| {}, None)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_some_and(CancelToken::is_cancelled)
+    }
+
+    fn directory_done(&self, dir: &Path, entries: usize) {
+        self.dirs_visited.set(self.dirs_visited.get() + 1);
+        self.entries.set(self.entries.get() + entries);
+        (self.on_progress)(&ScanProgress {
+            dirs_visited: self.dirs_visited.get(),
+            entries: self.entries.get(),
+            current_path: dir.to_string_lossy().to_string(),
+        });
+    }
+}
+
+/// Which directories (and bookmark files and Steam libraries) a rescan had to read again and which subtrees it reused from the cache.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RescanReport {
+    /// Directories whose fingerprint changed (or were new) and were listed again.
+    pub reread: Vec<String>,
+    /// Roots of subtrees where every directory matched its cached fingerprint.
+    pub skipped: Vec<String>,
+    /// One report per `ScanPath` for walks over whole scan paths; empty when only
+    /// single directories were refreshed.
+    pub diagnostics: Vec<ScanPathReport>,
+}
+
+/// Cached listing of a single directory, reused while its fingerprint is unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DirSnapshot {
+    /// Modification time in nanoseconds since the epoch; 0 when unavailable (never matches).
+    modified: u64,
+    /// Children left out by the visibility policy.
+    hidden: u32,
+    /// Entries located directly in this directory, before cross-path deduplication.
+    entries: Vec<AppEntry>,
+    /// Visible subdirectories, scanned recursively.
+    subdirs: Vec<String>,
+}
+
+/// Per-`ScanPath` directory snapshots keyed by `normalize_entry_key(dir)`.
+type DirSnapshots = HashMap<String, DirSnapshot>;
+
+struct ScanOptions<'a> {
+    root: PathBuf,
+    extensions: HashSet<String>,
     include_folders: bool,
+    exclude: Gitignore,
+    max_depth: Option<u32>,
+    follow_links: bool,
+    policy: &'a dyn VisibilityPolicy,
+    monitor: &'a ScanMonitor<'a>,
+}
+
+impl<'a> ScanOptions<'a> {
+    fn new(
+        sp: &ScanPath,
+        policy: &'a dyn VisibilityPolicy,
+        monitor: &'a ScanMonitor<'a>,
+    ) -> Self {
+        let root = sp.expanded_path();
+        Self {
+            exclude: build_exclude(&root, &sp.exclude),
+            root: PathBuf::from(root),
+            extensions: sp.extensions.iter().map(|e| e.to_lowercase()).collect(),
+            include_folders: sp.include_folders,
+            max_depth: sp.max_depth,
+            follow_links: sp.follow_links,
+            policy,
+            monitor,
+        }
+    }
+
+    fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        self.exclude.matched(path, is_dir).is_ignore()
+    }
+
+    /// Whether subdirectories of `dir` are still within `max_depth`.
+    fn descends_into(&self, dir: &Path) -> bool {
+        let Some(max_depth) = self.max_depth else {
+            return true;
+        };
+        let depth = dir
+            .strip_prefix(&self.root)
+            .map(|rel| rel.components().count())
+            .unwrap_or(0);
+        depth < max_depth as usize
+    }
+}
+
+/// Compile exclude patterns; invalid lines are skipped.
+fn build_exclude(root: &str, patterns: &[String]) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    let _ = builder.case_insensitive(cfg!(windows));
+    for pattern in patterns {
+        let pattern = pattern.trim();
+        if !pattern.is_empty() {
+            let _ = builder.add_line(None, pattern);
+        }
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// Walk every scan path. A cancelled walk returns whatever was read so far.
+fn scan_directories(
+    scan_paths: &[ScanPath],
+    show_hidden_system: bool,
+    previous: Option<&[DirSnapshots]>,
+    monitor: &ScanMonitor,
+) -> (Vec<DirSnapshots>, RescanReport) {
+    let policy = visibility::policy_for(show_hidden_system);
+    let mut roots = Vec::with_capacity(scan_paths.len());
+    let mut report = RescanReport::default();
+
+    for (i, sp) in scan_paths.iter().enumerate() {
+        let opts = ScanOptions::new(sp, policy, monitor);
+        let prev = previous.and_then(|p| p.get(i));
+        let root = opts.root.as_path();
+        let mut snapshots = DirSnapshots::new();
+        let started = Instant::now();
+        let mut walk = WalkState::new(&root.to_string_lossy());
+        if rescan_directory(root, &opts, prev, &mut snapshots, &mut walk, &mut report) {
+            report.skipped.push(root.to_string_lossy().to_string());
+        }
+        walk.stats.elapsed_ms = started.elapsed().as_millis() as u64;
+        report.diagnostics.push(walk.stats);
+        roots.push(snapshots);
+    }
+
+    (roots, report)
+}
+
+/// Scan every index source (directories, bookmark files and Steam), reusing unchanged parts
+/// of `previous`.
+fn scan_sources(
+    paths: &PathsConfig,
     show_hidden_system: bool,
-    entries: &mut Vec<AppEntry>,
-    seen: &mut std::collections::HashSet<String>,
+    previous: Option<&IndexCache>,
+    monitor: &ScanMonitor,
+) -> (Vec<AppEntry>, IndexCache, RescanReport) {
+    let (roots, mut report) = scan_directories(
+        &paths.scan,
+        show_hidden_system,
+        previous.map(|c| c.roots.as_slice()),
+        monitor,
+    );
+    // A cancelled scan is discarded by the caller, so skip the remaining sources
+    let bookmarks = if monitor.is_cancelled() {
+        Vec::new()
+    } else {
+        rescan_bookmarks(
+            &paths.bookmarks,
+            previous.map(|c| c.bookmarks.as_slice()),
+            &mut report,
+        )
+    };
+    let steam = if monitor.is_cancelled() {
+        None
+    } else {
+        rescan_steam(
+            paths.expanded_steam_dir().as_deref(),
+            previous.and_then(|c| c.steam.as_ref()),
+            &mut report,
+        )
+    };
+    let cache = IndexCache::new(
+        roots,
+        bookmarks,
+        steam,
+        compute_config_hash(paths, show_hidden_system),
+    );
+    let entries = cache.entries(paths);
+    (entries, cache, report)
+}
+
+/// Cached contents of one bookmarks file, reused while its size and mtime are unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BookmarkSnapshot {
+    /// Modification time in nanoseconds since the epoch; 0 when unavailable (never matches).
+    modified: u64,
+    len: u64,
+    entries: Vec<AppEntry>,
+}
+
+impl BookmarkSnapshot {
+    fn read(path: &Path) -> Self {
+        let meta = std::fs::metadata(path).ok();
+        Self {
+            modified: meta
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .map(nanos_since_epoch)
+                .unwrap_or(0),
+            len: meta.map(|m| m.len()).unwrap_or(0),
+            entries: bookmarks::read_bookmarks(path).unwrap_or_default(),
+        }
+    }
+
+    fn is_current(&self, path: &Path) -> bool {
+        let Ok(meta) = std::fs::metadata(path) else {
+            return false;
+        };
+        let modified = meta.modified().map(nanos_since_epoch).unwrap_or(0);
+        self.modified != 0 && self.modified == modified && self.len == meta.len()
+    }
+}
+
+/// One snapshot per bookmark source, in config order. Missing files yield no entries.
+fn rescan_bookmarks(
+    sources: &[BookmarkSource],
+    previous: Option<&[BookmarkSnapshot]>,
+    report: &mut RescanReport,
+) -> Vec<BookmarkSnapshot> {
+    sources
+        .iter()
+        .enumerate()
+        .map(|(i, source)| {
+            let expanded = source.expanded_path();
+            let path = Path::new(&expanded);
+            if let Some(prev) = previous.and_then(|p| p.get(i))
+                && prev.is_current(path)
+            {
+                report.skipped.push(expanded);
+                return prev.clone();
+            }
+            let snapshot = BookmarkSnapshot::read(path);
+            report.reread.push(expanded);
+            snapshot
+        })
+        .collect()
+}
+
+/// Re-read bookmark files named in `changed`.
+fn apply_bookmark_changes(
+    sources: &[BookmarkSource],
+    snapshots: &mut [BookmarkSnapshot],
+    changed: &[PathBuf],
+    report: &mut RescanReport,
 ) {
-    let Ok(read_dir) = std::fs::read_dir(dir) else {
-        return;
+    let changed: HashSet<String> = changed
+        .iter()
+        .map(|p| normalize_entry_key(&p.to_string_lossy()))
+        .collect();
+    for (source, snapshot) in sources.iter().zip(snapshots.iter_mut()) {
+        let expanded = source.expanded_path();
+        if changed.contains(&normalize_entry_key(&expanded)) {
+            *snapshot = BookmarkSnapshot::read(Path::new(&expanded));
+            report.reread.push(expanded);
+        }
+    }
+}
+
+/// Cached apps of a Steam installation, reused while `libraryfolders.vdf` and the `steamapps`
+/// folder of every library keep their modification times.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SteamSnapshot {
+    /// Path and modification time (nanoseconds since the epoch, 0 when unavailable) of each
+    /// file or folder the listing depends on.
+    stamps: Vec<(String, u64)>,
+    entries: Vec<AppEntry>,
+}
+
+impl SteamSnapshot {
+    fn read(steam_dir: &Path) -> Self {
+        let libraries = steam::library_folders(steam_dir);
+        let stamps = std::iter::once(steam::library_folders_file(steam_dir))
+            .chain(libraries.iter().map(|l| steam::steamapps_dir(l)))
+            .map(|path| {
+                let modified = path_modified(&path);
+                (path.to_string_lossy().to_string(), modified)
+            })
+            .collect();
+        Self {
+            stamps,
+            entries: steam::read_libraries(&libraries),
+        }
+    }
+
+    fn is_current(&self) -> bool {
+        self.stamps
+            .iter()
+            .all(|(path, modified)| *modified != 0 && path_modified(Path::new(path)) == *modified)
+    }
+}
+
+/// Snapshot of the Steam installation at `steam_dir`; None when Steam is not configured.
+fn rescan_steam(
+    steam_dir: Option<&str>,
+    previous: Option<&SteamSnapshot>,
+    report: &mut RescanReport,
+) -> Option<SteamSnapshot> {
+    let steam_dir = steam_dir?;
+    if let Some(prev) = previous
+        && prev.is_current()
+    {
+        report.skipped.push(steam_dir.to_string());
+        return Some(prev.clone());
+    }
+    report.reread.push(steam_dir.to_string());
+    Some(SteamSnapshot::read(Path::new(steam_dir)))
+}
+
+/// Bookkeeping for one walk over a scan path.
+struct WalkState {
+    /// Identities of directories already scanned; reaching one again through a link
+    /// (e.g. a junction to an ancestor) adds nothing, which breaks cycles.
+    visited: HashSet<PathBuf>,
+    stats: ScanPathReport,
+}
+
+impl WalkState {
+    fn new(root: &str) -> Self {
+        Self {
+            visited: HashSet::new(),
+            stats: ScanPathReport {
+                path: root.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Rebuild the snapshot for `dir` and its subtree into `snapshots`.
+/// Returns true when the whole subtree was reused from `previous`.
+fn rescan_directory(
+    dir: &Path,
+    opts: &ScanOptions,
+    previous: Option<&DirSnapshots>,
+    snapshots: &mut DirSnapshots,
+    walk: &mut WalkState,
+    report: &mut RescanReport,
+) -> bool {
+    if opts.monitor.is_cancelled() {
+        return false;
+    }
+    if !walk.visited.insert(dir_identity(dir)) {
+        return true;
+    }
+    let key = normalize_entry_key(&dir.to_string_lossy());
+    let modified = path_modified(dir);
+    let cached = previous
+        .and_then(|p| p.get(&key))
+        .filter(|s| modified != 0 && s.modified == modified);
+
+    let (snapshot, unchanged) = match cached {
+        Some(s) => (s.clone(), true),
+        None => {
+            report.reread.push(dir.to_string_lossy().to_string());
+            match read_directory(dir, opts, modified) {
+                Ok(s) => (s, false),
+                Err(e) => {
+                    let error = ScanError::new(dir.to_string_lossy().to_string(), &e);
+                    walk.stats.errors.push(error);
+                    return false;
+                }
+            }
+        }
+    };
+    walk.stats.dirs_visited += 1;
+    walk.stats.entries += snapshot.entries.len();
+    walk.stats.hidden_skipped += snapshot.hidden as usize;
+    opts.monitor.directory_done(dir, snapshot.entries.len());
+
+    let mut subtree_unchanged = unchanged;
+    let mut skipped_children = Vec::new();
+    for sub in &snapshot.subdirs {
+        if rescan_directory(Path::new(sub), opts, previous, snapshots, walk, report) {
+            skipped_children.push(sub.clone());
+        } else {
+            subtree_unchanged = false;
+        }
+    }
+    // Only report the topmost unchanged subtrees; the caller reports this one otherwise.
+    if !subtree_unchanged {
+        report.skipped.extend(skipped_children);
+    }
+
+    snapshots.insert(key, snapshot);
+    subtree_unchanged
+}
+
+/// List a single directory (non-recursive) into a snapshot.
+fn read_directory(
+    dir: &Path,
+    opts: &ScanOptions,
+    modified: u64,
+) -> std::io::Result<DirSnapshot> {
+    let read_dir = std::fs::read_dir(dir)?;
+    let visibility = DirVisibility::new(opts.policy, dir);
+    let descend = opts.descends_into(dir);
+    let mut snapshot = DirSnapshot {
+        modified,
+        hidden: 0,
+        entries: Vec::new(),
+        subdirs: Vec::new(),
     };
 
     for entry in read_dir.flatten() {
         let path = entry.path();
-        if !show_hidden_system && !is_visible_entry(&path) {
+        if !visibility.is_visible(&path) {
+            snapshot.hidden = snapshot.hidden.saturating_add(1);
             continue;
         }
-        if path.is_dir() {
-            if include_folders {
+        let meta = std::fs::metadata(&path).ok();
+        let is_dir = meta.as_ref().is_some_and(|m| m.is_dir());
+        if opts.is_excluded(&path, is_dir) {
+            continue;
+        }
+        let modified = meta
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        if is_dir {
+            let is_link = entry.file_type().is_ok_and(|t| t.is_symlink());
+            if opts.include_folders {
                 let name = path
                     .file_name()
                     .and_then(|s| s.to_str())
                     .unwrap_or("")
                     .to_string();
                 if !name.is_empty() {
-                    let key = normalize_entry_key(&path.to_string_lossy());
-                    if seen.insert(key) {
-                        entries.push(AppEntry {
-                            name,
-                            target_path: path.to_string_lossy().to_string(),
-                            is_folder: true,
-                        });
-                    }
+                    snapshot.entries.push(AppEntry {
+                        name,
+                        target_path: path.to_string_lossy().to_string(),
+                        is_folder: true,
+                        kind: EntryKind::Folder,
+                        modified,
+                        ..Default::default()
+                    });
                 }
             }
-            scan_directory_with_extensions(
-                &path,
-                extensions,
-                include_folders,
-                show_hidden_system,
-                entries,
-                seen,
-            );
+            if descend && (opts.follow_links || !is_link) {
+                snapshot.subdirs.push(path.to_string_lossy().to_string());
+            }
         } else {
             let ext = path
                 .extension()
                 .and_then(|e| e.to_str())
                 .map(|e| format!(".{}", e.to_lowercase()));
             if let Some(ext) = ext
-                && extensions.contains(&ext) {
+                && opts.extensions.contains(&ext) {
                     let name = path
                         .file_stem()
                         .and_then(|s| s.to_str())
                         .unwrap_or("")
                         .to_string();
-                    let key = normalize_entry_key(&path.to_string_lossy());
-                    if !name.is_empty() && seen.insert(key) {
-                        entries.push(AppEntry {
+                    if !name.is_empty() {
+                        let (shortcut, url) = match ext.as_str() {
+                            ".lnk" => (shortcut::read_shortcut(&path), None),
+                            ".url" => (None, shortcut::read_url_shortcut(&path)),
+                            _ => (None, None),
+                        };
+                        let keywords = version_keywords(&path, &ext, &name, shortcut.as_ref());
+                        let target_path = path.to_string_lossy().to_string();
+                        snapshot.entries.push(AppEntry {
                             name,
-                            target_path: path.to_string_lossy().to_string(),
+                            kind: EntryKind::for_path(&target_path, false),
+                            target_path,
                             is_folder: false,
+                            size: meta.as_ref().map(|m| m.len()),
+                            modified,
+                            shortcut,
+                            url,
+                            keywords,
+                            ..Default::default()
                         });
                     }
                 }
         }
     }
-}
 
-fn is_visible_entry(path: &Path) -> bool {
-    let Ok(meta) = std::fs::metadata(path) else {
-        return true;
-    };
-    let attrs = meta.file_attributes();
-    let hidden = (attrs & FILE_ATTRIBUTE_HIDDEN.0) != 0;
-    let system = (attrs & FILE_ATTRIBUTE_SYSTEM.0) != 0;
-    !hidden && !system
-}
+    Ok(snapshot)
+}
+
+/// Version resource names of an `.exe`, or of a shortcut's `.exe` target when it can be
+/// found. Names equal to the entry name are left out.
+fn version_keywords(
+    path: &Path,
+    ext: &str,
+    name: &str,
+    shortcut: Option<&ShortcutInfo>,
+) -> Vec<String> {
+    let info = match ext {
+        ".exe" => version_info::read_version_info(path),
+        ".lnk" => shortcut
+            .and_then(|s| s.target.as_deref())
+            .map(config::expand_path)
+            .filter(|target| target.to_lowercase().ends_with(".exe"))
+            .and_then(|target| version_info::read_version_info(Path::new(&target))),
+        _ => None,
+    };
+    let mut keywords = info.map(|i| i.keywords()).unwrap_or_default();
+    keywords.retain(|k| k.to_lowercase() != name.to_lowercase());
+    keywords
+}
+
+/// Identity of a directory regardless of the path it was reached by.
+/// Symbolic links and junctions resolve to their target.
+fn dir_identity(dir: &Path) -> PathBuf {
+    std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf())
+}
+
+fn path_modified(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(nanos_since_epoch)
+        .unwrap_or(0)
+}
+
+fn nanos_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Re-read the directories affected by `changed` paths (created, removed or renamed)
+/// in-place in `roots`, leaving every other snapshot untouched.
+fn apply_path_changes(
+    scan_paths: &[ScanPath],
+    show_hidden_system: bool,
+    roots: &mut [DirSnapshots],
+    changed: &[PathBuf],
+) -> RescanReport {
+    let policy = visibility::policy_for(show_hidden_system);
+    let monitor = ScanMonitor::silent();
+    let mut report = RescanReport::default();
+
+    for (sp, snapshots) in scan_paths.iter().zip(roots.iter_mut()) {
+        let opts = ScanOptions::new(sp, policy, &monitor);
+        let dirs = affected_dirs(&opts.root, &opts, snapshots, changed);
+        for dir in dirs {
+            refresh_directory(&dir, &opts, snapshots, &mut report);
+        }
+    }
+
+    report
+}
+
+/// Parent directories of `changed` paths that belong to this scan root and may alter its entries.
+fn affected_dirs(
+    root: &Path,
+    opts: &ScanOptions,
+    snapshots: &DirSnapshots,
+    changed: &[PathBuf],
+) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for path in changed {
+        if !path.starts_with(root) {
+            continue;
+        }
+        let Some(parent) = path.parent() else {
+            continue;
+        };
+        // Unknown parents are hidden or new; a new one is covered by its own parent's event.
+        let Some(snapshot) = snapshots.get(&normalize_entry_key(&parent.to_string_lossy())) else {
+            continue;
+        };
+        let path_str = path.to_string_lossy();
+        let matches_ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| opts.extensions.contains(&format!(".{}", e.to_lowercase())));
+        let known = snapshot.subdirs.iter().any(|d| *d == path_str)
+            || snapshot.entries.iter().any(|e| e.target_path == path_str);
+        if (matches_ext || known || path.is_dir()) && !dirs.iter().any(|d| d == parent) {
+            dirs.push(parent.to_path_buf());
+        }
+    }
+    dirs
+}
+
+/// Force `dir` to be re-read, dropping its cached subtree so removed subdirectories vanish.
+/// Descendants whose fingerprint still matches are reused.
+fn refresh_directory(
+    dir: &Path,
+    opts: &ScanOptions,
+    snapshots: &mut DirSnapshots,
+    report: &mut RescanReport,
+) {
+    let key = normalize_entry_key(&dir.to_string_lossy());
+    let mut previous = DirSnapshots::new();
+    take_subtree(snapshots, &key, &mut previous);
+    previous.remove(&key);
+    let mut walk = WalkState::new(&opts.root.to_string_lossy());
+    walk.visited = dir
+        .ancestors()
+        .skip(1)
+        .take_while(|a| a.starts_with(&opts.root))
+        .map(dir_identity)
+        .collect();
+    rescan_directory(dir, opts, Some(&previous), snapshots, &mut walk, report);
+}
+
+fn take_subtree(snapshots: &mut DirSnapshots, key: &str, out: &mut DirSnapshots) {
+    let Some(snapshot) = snapshots.remove(key) else {
+        return;
+    };
+    for sub in &snapshot.subdirs {
+        take_subtree(snapshots, &normalize_entry_key(sub), out);
+    }
+    out.insert(key.to_string(), snapshot);
+}
+
+/// Expand snapshots into the flat entry list, deduplicating by normalized path
+/// and, with `by_target`, by launch target.
+fn flatten_snapshots(
+    scan_paths: &[ScanPath],
+    roots: &[DirSnapshots],
+    by_target: bool,
+) -> Vec<AppEntry> {
+    let mut dedup = EntryDeduper::new(by_target);
+    flatten_roots(scan_paths, roots, &mut dedup);
+    dedup.entries
+}
+
+/// Push the entries of every scan path's snapshots into `dedup`, preferred paths first.
+fn flatten_roots(scan_paths: &[ScanPath], roots: &[DirSnapshots], dedup: &mut EntryDeduper) {
+    for i in source_order(scan_paths) {
+        if let Some(snapshots) = roots.get(i) {
+            let root = scan_paths[i].expanded_path();
+            flatten_directory(Path::new(&root), snapshots, scan_paths[i].weight, dedup);
+        }
+    }
+}
+
+/// Scan path indices with preferred paths first, otherwise in config order.
+fn source_order(scan_paths: &[ScanPath]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..scan_paths.len()).collect();
+    order.sort_by_key(|&i| !scan_paths[i].preferred);
+    order
+}
+
+/// Snapshots hold entries without their scan path's `weight`; it is applied here so a
+/// snapshot can be reused as is.
+fn flatten_directory(dir: &Path, snapshots: &DirSnapshots, weight: i32, dedup: &mut EntryDeduper) {
+    let Some(snapshot) = snapshots.get(&normalize_entry_key(&dir.to_string_lossy())) else {
+        return;
+    };
+    for entry in &snapshot.entries {
+        if let Some(kept) = dedup.push(entry) {
+            kept.weight = weight;
+        }
+    }
+    for sub in &snapshot.subdirs {
+        flatten_directory(Path::new(sub), snapshots, weight, dedup);
+    }
+}
+
+/// Collects entries, keeping the first of each path and, when enabled, the first of each
+/// launch target. Later duplicates of a target are recorded as aliases of the kept entry.
+struct EntryDeduper {
+    by_target: bool,
+    entries: Vec<AppEntry>,
+    seen_paths: HashSet<String>,
+    targets: HashMap<String, usize>,
+}
+
+impl EntryDeduper {
+    fn new(by_target: bool) -> Self {
+        Self {
+            by_target,
+            entries: Vec::new(),
+            seen_paths: HashSet::new(),
+            targets: HashMap::new(),
+        }
+    }
+
+    /// Returns the added copy of `entry`; None when it was a duplicate.
+    fn push(&mut self, entry: &AppEntry) -> Option<&mut AppEntry> {
+        if !self.seen_paths.insert(normalize_entry_key(&entry.target_path)) {
+            return None;
+        }
+        if self.by_target
+            && let Some(key) = target_key(entry)
+        {
+            if let Some(&kept) = self.targets.get(&key) {
+                self.entries[kept].aliases.push(entry.target_path.clone());
+                return None;
+            }
+            self.targets.insert(key, self.entries.len());
+        }
+        self.entries.push(entry.clone());
+        self.entries.last_mut()
+    }
+}
+
+/// What launching the entry opens: a shortcut's expanded target and arguments, a URL, or
+/// the file itself. A shortcut without arguments has the same key as its target file.
+/// None for folders and shortcuts whose target is unknown.
+fn target_key(entry: &AppEntry) -> Option<String> {
+    if entry.is_folder {
+        return None;
+    }
+    if let Some(url) = &entry.url {
+        return Some(format!("url:{}", url.url));
+    }
+    if entry.kind == EntryKind::AppShortcut {
+        let shortcut = entry.shortcut.as_ref()?;
+        let target = normalize_entry_key(&config::expand_path(shortcut.target.as_deref()?));
+        let arguments = shortcut.arguments.as_deref().unwrap_or("").trim();
+        if arguments.is_empty() {
+            return Some(target);
+        }
+        return Some(format!("{}\0{}", target, arguments));
+    }
+    Some(normalize_entry_key(&entry.target_path))
+}
+
+pub(crate) fn normalize_entry_key(path: &str) -> String {
+    path.trim().replace('/', "\\").to_lowercase()
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexCache {
+    built_at: u64,
+    config_hash: u64,
+    /// One snapshot map per `ScanPath`, in config order.
+    roots: Vec<DirSnapshots>,
+    /// One snapshot per `BookmarkSource`, in config order.
+    bookmarks: Vec<BookmarkSnapshot>,
+    /// Set when `paths.steam_dir` is configured.
+    #[serde(default)]
+    steam: Option<SteamSnapshot>,
+}
+
+impl IndexCache {
+    fn new(
+        roots: Vec<DirSnapshots>,
+        bookmarks: Vec<BookmarkSnapshot>,
+        steam: Option<SteamSnapshot>,
+        config_hash: u64,
+    ) -> Self {
+        Self {
+            built_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            config_hash,
+            roots,
+            bookmarks,
+            steam,
+        }
+    }
+
+    /// Directory entries followed by bookmarks and Steam apps, deduplicated by normalized path
+    /// and, when `dedup_targets` is on, by launch target.
+    fn entries(&self, paths: &PathsConfig) -> Vec<AppEntry> {
+        let mut dedup = EntryDeduper::new(paths.dedup_targets);
+        flatten_roots(&paths.scan, &self.roots, &mut dedup);
+        for snapshot in &self.bookmarks {
+            for entry in &snapshot.entries {
+                dedup.push(entry);
+            }
+        }
+        for entry in self.steam.iter().flat_map(|s| &s.entries) {
+            dedup.push(entry);
+        }
+        dedup.entries
+    }
+}
+
+/// Scan paths are hashed as written, before variable expansion.
+fn compute_config_hash(paths: &PathsConfig, show_hidden_system: bool) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for sp in &paths.scan {
+        sp.path.hash(&mut hasher);
+        sp.extensions.hash(&mut hasher);
+        sp.include_folders.hash(&mut hasher);
+        sp.exclude.hash(&mut hasher);
+        sp.max_depth.hash(&mut hasher);
+        sp.follow_links.hash(&mut hasher);
+    }
+    for source in &paths.bookmarks {
+        source.path.hash(&mut hasher);
+    }
+    if let Some(steam_dir) = &paths.steam_dir {
+        steam_dir.hash(&mut hasher);
+    }
+    show_hidden_system.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn cache_path() -> Option<PathBuf> {
+    Config::config_dir().map(|p| p.join("index.bin"))
+}
+
+fn icon_cache_path() -> Option<PathBuf> {
+    Config::config_dir().map(|p| p.join("icons.bin"))
+}
+
+fn invalidate_icon_cache_at(path: &Path) {
+    let _ = std::fs::remove_file(path);
+}
+
+fn invalidate_icon_cache() {
+    let Some(path) = icon_cache_path() else {
+        return;
+    };
+    invalidate_icon_cache_at(&path);
+}
+
+/// Entry-level difference between two indexes, keyed by normalized target path.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexDiff {
+    pub added: Vec<AppEntry>,
+    pub removed: Vec<AppEntry>,
+    /// Entries whose path is unchanged but whose other fields differ (new values).
+    pub changed: Vec<AppEntry>,
+}
+
+impl IndexDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+pub fn diff_entries(old: &[AppEntry], new: &[AppEntry]) -> IndexDiff {
+    let old_by_key: HashMap<String, &AppEntry> = old
+        .iter()
+        .map(|e| (normalize_entry_key(&e.target_path), e))
+        .collect();
+    let new_keys: HashSet<String> = new
+        .iter()
+        .map(|e| normalize_entry_key(&e.target_path))
+        .collect();
+
+    let mut diff = IndexDiff::default();
+    for entry in new {
+        match old_by_key.get(&normalize_entry_key(&entry.target_path)) {
+            None => diff.added.push(entry.clone()),
+            Some(prev) if !same_entry(prev, entry) => diff.changed.push(entry.clone()),
+            Some(_) => {}
+        }
+    }
+    diff.removed = old
+        .iter()
+        .filter(|e| !new_keys.contains(&normalize_entry_key(&e.target_path)))
+        .cloned()
+        .collect();
+    diff
+}
+
+/// Outcome of the background rescan started by `load_or_scan`.
+#[derive(Debug, Clone)]
+pub struct RescanResult {
+    /// The complete, freshly scanned entry list.
+    pub entries: Vec<AppEntry>,
+    /// Difference from the entries `load_or_scan` returned.
+    pub diff: IndexDiff,
+    pub report: RescanReport,
+}
+
+/// Load the cache immediately and rescan changed directories in the background.
+/// Returns (entries, changed) where changed=true means no usable cache existed.
+/// When the cache was used, `on_rescan` is called from the rescan thread once it finishes.
+pub fn load_or_scan<F>(
+    paths: &PathsConfig,
+    show_hidden_system: bool,
+    on_rescan: F,
+) -> (Vec<AppEntry>, bool)
+where
+    F: FnOnce(RescanResult) + Send + 'static,
+{
+    if let Some(bytes) = cache_path().and_then(|p| std::fs::read(p).ok())
+        && let Some(entries) = read_cached_entries(&bytes, paths, show_hidden_system)
+    {
+        spawn_background_rescan(paths.clone(), show_hidden_system, bytes, on_rescan);
+        return (entries, false);
+    }
+
+    let _lock = lock_cache();
+    let (entries, cache, report) =
+        scan_sources(paths, show_hidden_system, None, &ScanMonitor::silent());
+    save_cache(&cache, paths);
+    diagnostics::save_diagnostics(&ScanDiagnostics::new(report.diagnostics));
+    (entries, true)
+}
+
+fn entries_equal(a: &[AppEntry], b: &[AppEntry]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).all(|(x, y)| same_entry(x, y))
+}
+
+fn same_entry(x: &AppEntry, y: &AppEntry) -> bool {
+    x.name == y.name
+        && x.target_path == y.target_path
+        && x.is_folder == y.is_folder
+        && x.kind == y.kind
+        && x.shortcut == y.shortcut
+        && x.url == y.url
+        && x.context == y.context
+        && x.aliases == y.aliases
+        && x.keywords == y.keywords
+        && x.weight == y.weight
+}
+
+fn lock_cache() -> MutexGuard<'static, ()> {
+    CACHE_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Write `index.bin`. Callers hold `lock_cache` from loading the cache they changed.
+fn save_cache(cache: &IndexCache, paths: &PathsConfig) {
+    let Some(path) = cache_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+
+    let Some(bytes) = encode_cache(cache, paths) else {
+        return;
+    };
+
+    let n = CACHE_WRITES.fetch_add(1, Ordering::Relaxed);
+    let tmp_path = path.with_extension(format!("bin.{}.{}.tmp", std::process::id(), n));
+    if std::fs::write(&tmp_path, &bytes).is_ok() {
+        let _ = std::fs::remove_file(&path);
+        if std::fs::rename(&tmp_path, &path).is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+    } else {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+}
+
+/// Force rebuild: scan and save cache, regardless of existing cache.
+/// Called from settings dialog (Phase 5).
+/// Returns None when `cancel` was triggered; the saved index is left untouched then.
+pub fn rebuild_and_save(
+    paths: &PathsConfig,
+    show_hidden_system: bool,
+    on_progress: &dyn Fn(&ScanProgress),
+    cancel: &CancelToken,
+) -> Option<Vec<AppEntry>> {
+    let monitor = ScanMonitor::new(on_progress, Some(cancel));
+    let (entries, cache, report) = scan_sources(paths, show_hidden_system, None, &monitor);
+    if cancel.is_cancelled() {
+        return None;
+    }
+    let _lock = lock_cache();
+    save_cache(&cache, paths);
+    diagnostics::save_diagnostics(&ScanDiagnostics::new(report.diagnostics));
+    Some(entries)
+}
+
+/// The saved index as JSON; None when there is no readable cache.
+pub fn export_index_json() -> Option<String> {
+    let bytes = std::fs::read(cache_path()?).ok()?;
+    let cache = CacheFile::parse(&bytes)?.snapshots()?;
+    serde_json::to_string_pretty(&cache).ok()
+}
+
+/// Replace the saved index with an `export_index_json` document and return its entries.
+/// Only an index built for the current scan settings is accepted.
+pub fn import_index_json(
+    json: &str,
+    paths: &PathsConfig,
+    show_hidden_system: bool,
+) -> Result<Vec<AppEntry>, ImportError> {
+    let cache = parse_index_json(json, paths, show_hidden_system)?;
+    let entries = cache.entries(paths);
+    let _lock = lock_cache();
+    save_cache(&cache, paths);
+    Ok(entries)
+}
+
+fn parse_index_json(
+    json: &str,
+    paths: &PathsConfig,
+    show_hidden_system: bool,
+) -> Result<IndexCache, ImportError> {
+    let cache: IndexCache = serde_json::from_str(json)?;
+    if cache.roots.len() != paths.scan.len() || cache.bookmarks.len() != paths.bookmarks.len() {
+        return Err(ImportError::Invalid(
+            "スキャンパス・ブックマークの数が現在の設定と一致しません".to_string(),
+        ));
+    }
+    if cache.config_hash != compute_config_hash(paths, show_hidden_system) {
+        return Err(ImportError::Invalid(
+            "現在のインデックス設定とは異なる設定で作成されたインデックスです".to_string(),
+        ));
+    }
+    Ok(cache)
+}
+
+fn load_cache(paths: &PathsConfig, show_hidden_system: bool) -> Option<IndexCache> {
+    let path = cache_path()?;
+    let bytes = std::fs::read(path).ok()?;
+    read_cache(&bytes, paths, show_hidden_system)
+}
+
+/// The flat entry list of a cache file matching the configuration. The current format is
+/// read without decoding the snapshots; a v1 cache is migrated first.
+fn read_cached_entries(
+    bytes: &[u8],
+    paths: &PathsConfig,
+    show_hidden_system: bool,
+) -> Option<Vec<AppEntry>> {
+    match CacheFile::parse(bytes) {
+        Some(file) => (file.config_hash == compute_config_hash(paths, show_hidden_system))
+            .then(|| file.entries(paths))
+            .flatten(),
+        None => read_cache(bytes, paths, show_hidden_system).map(|cache| cache.entries(paths)),
+    }
+}
+
+/// Files with any other version are simply rebuilt.
+/// Other outdated versions are simply rebuilt.
+fn read_cache(bytes: &[u8], paths: &PathsConfig, show_hidden_system: bool) -> Option<IndexCache> {
+    if let Some(file) = CacheFile::parse(bytes) {
+        return (file.config_hash == compute_config_hash(paths, show_hidden_system))
+            .then(|| file.snapshots())
+            .flatten();
+    }
+    let legacy: IndexCacheV1 = deserialize_with_header(bytes, INDEX_MAGIC, INDEX_CACHE_VERSION_V1)?;
+    if Some(legacy.config_hash) != legacy_config_hash(paths, show_hidden_system) {
+        return None;
+    }
+    Some(migrate_v1(legacy, paths, show_hidden_system))
+}
+
+/// Layout of `index.bin` after the binfmt header:
+///
+/// `config_hash: u64 LE | entries_len: u64 LE | entries (entries_len bytes) | snapshots`
+///
+/// `entries` holds every snapshot entry once, in groups: one per scan path in config
+/// order, with its directories in flattening order, then one per bookmark source and one
+/// for Steam. `snapshots` is the rest of the `IndexCache`; each directory refers to a range
+/// of its scan path's group. Startup decodes only `entries` and flattens the groups; the
+/// snapshots are decoded by rescans, off the startup path.
+struct CacheFile<'a> {
+    config_hash: u64,
+    entries: &'a [u8],
+    snapshots: &'a [u8],
+}
+
+/// `IndexCache` without entries and config hash, as stored after the entry groups.
+#[derive(Serialize, Deserialize)]
+struct StoredSnapshots {
+    built_at: u64,
+    roots: Vec<HashMap<String, StoredDir>>,
+    /// Modification time and size of each bookmark file.
+    bookmarks: Vec<(u64, u64)>,
+    steam_stamps: Option<Vec<(String, u64)>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredDir {
+    modified: u64,
+    hidden: u32,
+    /// Start and length of the directory's entries in its scan path's group.
+    entries: (u32, u32),
+    subdirs: Vec<String>,
+}
+
+impl<'a> CacheFile<'a> {
+    fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let body = binfmt::payload(bytes, INDEX_MAGIC, INDEX_CACHE_VERSION)?;
+        let (hash, body) = body.split_first_chunk::<8>()?;
+        let (len, body) = body.split_first_chunk::<8>()?;
+        let len = usize::try_from(u64::from_le_bytes(*len)).ok()?;
+        if len > body.len() {
+            return None;
+        }
+        let (entries, snapshots) = body.split_at(len);
+        Some(Self {
+            config_hash: u64::from_le_bytes(*hash),
+            entries,
+            snapshots,
+        })
+    }
+
+    fn groups(&self) -> Option<Vec<Vec<AppEntry>>> {
+        bincode::deserialize(self.entries).ok()
+    }
+
+    /// The flat entry list, like `IndexCache::entries`, without decoding the snapshots.
+    fn entries(&self, paths: &PathsConfig) -> Option<Vec<AppEntry>> {
+        let groups = self.groups()?;
+        if groups.len() != paths.scan.len() + paths.bookmarks.len() + 1 {
+            return None;
+        }
+        let mut dedup = EntryDeduper::new(paths.dedup_targets);
+        for i in source_order(&paths.scan) {
+            for entry in &groups[i] {
+                if let Some(kept) = dedup.push(entry) {
+                    kept.weight = paths.scan[i].weight;
+                }
+            }
+        }
+        for entry in groups[paths.scan.len()..].iter().flatten() {
+            dedup.push(entry);
+        }
+        Some(dedup.entries)
+    }
+
+    fn snapshots(&self) -> Option<IndexCache> {
+        let stored: StoredSnapshots = bincode::deserialize(self.snapshots).ok()?;
+        let mut groups = self.groups()?.into_iter();
+        if groups.len() != stored.roots.len() + stored.bookmarks.len() + 1 {
+            return None;
+        }
+        let mut roots = Vec::with_capacity(stored.roots.len());
+        for dirs in stored.roots {
+            let group = groups.next()?;
+            let mut snapshots = DirSnapshots::with_capacity(dirs.len());
+            for (key, dir) in dirs {
+                let (start, len) = (dir.entries.0 as usize, dir.entries.1 as usize);
+                let entries = group.get(start..start.checked_add(len)?)?.to_vec();
+                snapshots.insert(
+                    key,
+                    DirSnapshot {
+                        modified: dir.modified,
+                        hidden: dir.hidden,
+                        entries,
+                        subdirs: dir.subdirs,
+                    },
+                );
+            }
+            roots.push(snapshots);
+        }
+        let bookmarks = stored
+            .bookmarks
+            .into_iter()
+            .zip(groups.by_ref())
+            .map(|((modified, len), entries)| BookmarkSnapshot {
+                modified,
+                len,
+                entries,
+            })
+            .collect();
+        let steam_entries = groups.next()?;
+        let steam = stored.steam_stamps.map(|stamps| SteamSnapshot {
+            stamps,
+            entries: steam_entries,
+        });
+        Some(IndexCache {
+            built_at: stored.built_at,
+            config_hash: self.config_hash,
+            roots,
+            bookmarks,
+            steam,
+        })
+    }
+}
+
+/// `cache` must be built for `paths`; the scan path roots give the flattening order.
+fn encode_cache(cache: &IndexCache, paths: &PathsConfig) -> Option<Vec<u8>> {
+    let mut groups: Vec<Vec<&AppEntry>> = Vec::new();
+    let mut roots = Vec::with_capacity(cache.roots.len());
+    for (sp, snapshots) in paths.scan.iter().zip(&cache.roots) {
+        let mut group = Vec::new();
+        let mut dirs = HashMap::new();
+        store_directory(Path::new(&sp.expanded_path()), snapshots, &mut group, &mut dirs);
+        groups.push(group);
+        roots.push(dirs);
+    }
+    groups.extend(cache.bookmarks.iter().map(|b| b.entries.iter().collect()));
+    groups.push(cache.steam.iter().flat_map(|s| &s.entries).collect());
+    let stored = StoredSnapshots {
+        built_at: cache.built_at,
+        roots,
+        bookmarks: cache.bookmarks.iter().map(|b| (b.modified, b.len)).collect(),
+        steam_stamps: cache.steam.as_ref().map(|s| s.stamps.clone()),
+    };
+
+    let entries = bincode::serialize(&groups).ok()?;
+    let snapshots = bincode::serialize(&stored).ok()?;
+    let mut body = Vec::with_capacity(16 + entries.len() + snapshots.len());
+    body.extend_from_slice(&cache.config_hash.to_le_bytes());
+    body.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+    body.extend_from_slice(&entries);
+    body.extend_from_slice(&snapshots);
+    Some(binfmt::with_header(INDEX_MAGIC, INDEX_CACHE_VERSION, &body))
+}
+
+/// Append the entries of `dir` and its subdirectories to `group` in the order
+/// `flatten_directory` visits them. Directories it never reaches are left out.
+fn store_directory<'a>(
+    dir: &Path,
+    snapshots: &'a DirSnapshots,
+    group: &mut Vec<&'a AppEntry>,
+    stored: &mut HashMap<String, StoredDir>,
+) {
+    let key = normalize_entry_key(&dir.to_string_lossy());
+    if stored.contains_key(&key) {
+        return;
+    }
+    let Some(snapshot) = snapshots.get(&key) else {
+        return;
+    };
+    let start = group.len() as u32;
+    group.extend(&snapshot.entries);
+    stored.insert(
+        key,
+        StoredDir {
+            modified: snapshot.modified,
+            hidden: snapshot.hidden,
+            entries: (start, snapshot.entries.len() as u32),
+            subdirs: snapshot.subdirs.clone(),
+        },
+    );
+    for sub in &snapshot.subdirs {
+        store_directory(Path::new(sub), snapshots, group, stored);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexCacheV1 {
+    built_at: u64,
+    entries: Vec<AppEntryV1>,
+    config_hash: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AppEntryV1 {
+    name: String,
+    target_path: String,
+    is_folder: bool,
+}
+
+/// The v1 config hash. None when the configuration uses settings v1 did not have,
+/// in which case a v1 cache cannot describe it.
+fn legacy_config_hash(paths: &PathsConfig, show_hidden_system: bool) -> Option<u64> {
+    let uses_newer_settings = !paths.bookmarks.is_empty()
+        || paths.steam_dir.is_some()
+        || paths
+            .scan
+            .iter()
+            .any(|sp| {
+                !sp.exclude.is_empty() || sp.max_depth.is_some() || !sp.follow_links
+            });
+    if uses_newer_settings {
+        return None;
+    }
+    let mut hasher = DefaultHasher::new();
+    for sp in &paths.scan {
+        sp.path.hash(&mut hasher);
+        sp.extensions.hash(&mut hasher);
+        sp.include_folders.hash(&mut hasher);
+    }
+    show_hidden_system.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// Place each v1 entry in an unstamped snapshot of the first root containing it.
+/// The entries are served at startup as before and the background rescan re-reads
+/// every directory, filling in kinds, metadata and shortcut details.
+fn migrate_v1(legacy: IndexCacheV1, paths: &PathsConfig, show_hidden_system: bool) -> IndexCache {
+    let root_keys: Vec<String> = paths
+        .scan
+        .iter()
+        .map(|sp| normalize_entry_key(&sp.expanded_path()))
+        .collect();
+    let mut roots: Vec<DirSnapshots> = vec![DirSnapshots::new(); paths.scan.len()];
+    for old in legacy.entries {
+        let key = normalize_entry_key(&old.target_path);
+        let Some(idx) = root_keys.iter().position(|root| {
+            key.strip_prefix(root.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('\\'))
+        }) else {
+            continue;
+        };
+        roots[idx]
+            .entry(root_keys[idx].clone())
+            .or_insert_with(|| DirSnapshot {
+                modified: 0,
+                hidden: 0,
+                entries: Vec::new(),
+                subdirs: Vec::new(),
+            })
+            .entries
+            .push(AppEntry {
+                kind: EntryKind::for_path(&old.target_path, old.is_folder),
+                name: old.name,
+                target_path: old.target_path,
+                is_folder: old.is_folder,
+                ..Default::default()
+            });
+    }
+    let mut cache = IndexCache::new(
+        roots,
+        Vec::new(),
+        None,
+        compute_config_hash(paths, show_hidden_system),
+    );
+    cache.built_at = legacy.built_at;
+    cache
+}
+
+/// Rescan against `cache`, saving it when any directory was re-read.
+/// The icon cache is dropped if the entries changed.
+fn rescan_with_cache(
+    paths: &PathsConfig,
+    show_hidden_system: bool,
+    cache: IndexCache,
+    cached_entries: &[AppEntry],
+) -> RescanResult {
+    let (entries, cache, report) =
+        scan_sources(paths, show_hidden_system, Some(&cache), &ScanMonitor::silent());
+    if !report.reread.is_empty() {
+        save_cache(&cache, paths);
+    }
+    diagnostics::save_diagnostics(&ScanDiagnostics::new(report.diagnostics.clone()));
+    let diff = if entries_equal(cached_entries, &entries) {
+        IndexDiff::default()
+    } else {
+        diff_entries(cached_entries, &entries)
+    };
+    if !diff.is_empty() {
+        invalidate_icon_cache();
+    }
+    RescanResult {
+        entries,
+        diff,
+        report,
+    }
+}
+
+/// Entries of the saved cache, flattened with the current scan path weights.
+/// Returns `None` if no cache matches the current configuration.
+pub fn cached_entries(paths: &PathsConfig, show_hidden_system: bool) -> Option<Vec<AppEntry>> {
+    let bytes = std::fs::read(cache_path()?).ok()?;
+    read_cached_entries(&bytes, paths, show_hidden_system)
+}
+
+/// Rescan against the saved cache; used for periodic rescans when watching is unavailable.
+/// Returns `None` if no cache matches the current configuration.
+pub fn rescan_cached(paths: &PathsConfig, show_hidden_system: bool) -> Option<RescanResult> {
+    let _lock = lock_cache();
+    let cache = load_cache(paths, show_hidden_system)?;
+    let cached_entries = cache.entries(paths);
+    Some(rescan_with_cache(
+        paths,
+        show_hidden_system,
+        cache,
+        &cached_entries,
+    ))
+}
+
+/// Apply filesystem change notifications to the saved cache and return the updated index.
+/// Returns `None` if no cache matches or none of the paths affected a scanned directory
+/// or bookmark file.
+pub fn apply_changes(
+    paths: &PathsConfig,
+    show_hidden_system: bool,
+    changed: &[PathBuf],
+) -> Option<RescanResult> {
+    let _lock = lock_cache();
+    let mut cache = load_cache(paths, show_hidden_system)?;
+    let cached_entries = cache.entries(paths);
+
+    let mut report =
+        apply_path_changes(&paths.scan, show_hidden_system, &mut cache.roots, changed);
+    apply_bookmark_changes(&paths.bookmarks, &mut cache.bookmarks, changed, &mut report);
+    if report.reread.is_empty() {
+        return None;
+    }
+
+    let entries = cache.entries(paths);
+    save_cache(&cache, paths);
+    let diff = diff_entries(&cached_entries, &entries);
+    if !diff.is_empty() {
+        invalidate_icon_cache();
+    }
+    Some(RescanResult {
+        entries,
+        diff,
+        report,
+    })
+}
+
+/// Incremental rescan of the scan paths rooted at, and bookmark files at, `unwatched`
+/// paths; used for periodic polling of sources the watcher could not watch. Returns `None`
+/// if no cache matches or nothing had to be read again.
+pub fn rescan_unwatched(
+    paths: &PathsConfig,
+    show_hidden_system: bool,
+    unwatched: &[PathBuf],
+) -> Option<RescanResult> {
+    let _lock = lock_cache();
+    let mut cache = load_cache(paths, show_hidden_system)?;
+    let cached_entries = cache.entries(paths);
+    let keys: HashSet<String> = unwatched
+        .iter()
+        .map(|p| normalize_entry_key(&p.to_string_lossy()))
+        .collect();
+
+    let policy = visibility::policy_for(show_hidden_system);
+    let monitor = ScanMonitor::silent();
+    let mut report = RescanReport::default();
+    for (sp, snapshots) in paths.scan.iter().zip(cache.roots.iter_mut()) {
+        let opts = ScanOptions::new(sp, policy, &monitor);
+        if !keys.contains(&normalize_entry_key(&opts.root.to_string_lossy())) {
+            continue;
+        }
+        let previous = std::mem::take(snapshots);
+        let mut walk = WalkState::new(&opts.root.to_string_lossy());
+        rescan_directory(&opts.root, &opts, Some(&previous), snapshots, &mut walk, &mut report);
+    }
+    for (source, snapshot) in paths.bookmarks.iter().zip(cache.bookmarks.iter_mut()) {
+        let expanded = source.expanded_path();
+        let path = Path::new(&expanded);
+        if keys.contains(&normalize_entry_key(&expanded)) && !snapshot.is_current(path) {
+            *snapshot = BookmarkSnapshot::read(path);
+            report.reread.push(expanded);
+        }
+    }
+    if report.reread.is_empty() {
+        return None;
+    }
+
+    let entries = cache.entries(paths);
+    save_cache(&cache, paths);
+    let diff = diff_entries(&cached_entries, &entries);
+    if !diff.is_empty() {
+        invalidate_icon_cache();
+    }
+    Some(RescanResult {
+        entries,
+        diff,
+        report,
+    })
+}
+
+/// Rescan in a background thread, decoding the snapshots of the cache file `bytes` there
+/// rather than on the startup path. The entries `load_or_scan` returned are rebuilt from
+/// the snapshots for the diff instead of being copied to the thread.
+fn spawn_background_rescan<F>(
+    paths: PathsConfig,
+    show_hidden_system: bool,
+    bytes: Vec<u8>,
+    on_rescan: F,
+) where
+    F: FnOnce(RescanResult) + Send + 'static,
+{
+    let _ = thread::Builder::new()
+        .name("snotra-index-rescan".to_string())
+        .spawn(move || {
+            let result = {
+                let _lock = lock_cache();
+                let Some(cache) = read_cache(&bytes, &paths, show_hidden_system) else {
+                    return;
+                };
+                drop(bytes);
+                let cached_entries = cache.entries(&paths);
+                rescan_with_cache(&paths, show_hidden_system, cache, &cached_entries)
+            };
+            on_rescan(result);
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binfmt::serialize_with_header;
+    use crate::visibility::ShowAll;
+    use std::fs;
+
+    /// Full (non-incremental) scan of a single directory tree.
+    fn scan_directory_with_extensions(
+        dir: &Path,
+        extensions: &HashSet<String>,
+        include_folders: bool,
+        policy: &dyn VisibilityPolicy,
+        entries: &mut Vec<AppEntry>,
+        seen: &mut HashSet<String>,
+    ) {
+        let opts = ScanOptions {
+            root: dir.to_path_buf(),
+            extensions: extensions.clone(),
+            include_folders,
+            exclude: Gitignore::empty(),
+            max_depth: None,
+            follow_links: true,
+            policy,
+            monitor: &ScanMonitor::silent(),
+        };
+        let mut snapshots = DirSnapshots::new();
+        let mut report = RescanReport::default();
+        let mut walk = WalkState::new(&dir.to_string_lossy());
+        rescan_directory(dir, &opts, None, &mut snapshots, &mut walk, &mut report);
+        let mut dedup = EntryDeduper::new(false);
+        flatten_directory(dir, &snapshots, 0, &mut dedup);
+        for entry in dedup.entries {
+            if seen.insert(normalize_entry_key(&entry.target_path)) {
+                entries.push(entry);
+            }
+        }
+    }
+
+    fn paths_config(scan: &[ScanPath], bookmarks: &[&Path]) -> PathsConfig {
+        PathsConfig {
+            scan: scan.to_vec(),
+            bookmarks: bookmarks
+                .iter()
+                .map(|p| BookmarkSource {
+                    path: p.to_string_lossy().to_string(),
+                })
+                .collect(),
+            ..Config::default().paths
+        }
+    }
+
+    fn scan_hash(scan: &[ScanPath]) -> u64 {
+        compute_config_hash(&paths_config(scan, &[]), false)
+    }
+
+    /// Scan all paths, reusing directories from `previous` whose fingerprint is unchanged.
+    /// `previous` must come from a cache built with the same config hash (same `ScanPath` order).
+    fn scan_incremental(
+        scan_paths: &[ScanPath],
+        show_hidden_system: bool,
+        previous: Option<&[DirSnapshots]>,
+    ) -> (Vec<AppEntry>, Vec<DirSnapshots>, RescanReport) {
+        let monitor = ScanMonitor::silent();
+        let (roots, report) = scan_directories(scan_paths, show_hidden_system, previous, &monitor);
+        let entries = flatten_snapshots(scan_paths, &roots, true);
+        (entries, roots, report)
+    }
+
+    fn full_scan(scan_paths: &[ScanPath], show_hidden_system: bool) -> Vec<AppEntry> {
+        scan_all(scan_paths, show_hidden_system, true, &|_| {}, &CancelToken::new()).expect("scan")
+    }
+
+    fn temp_dir(tag: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("snotra_idx_test_{}", tag));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn scan_with_extensions_filters_by_ext() {
+        let dir = temp_dir("ext_filter");
+        fs::write(dir.join("app.exe"), "").unwrap();
+        fs::write(dir.join("script.bat"), "").unwrap();
+        fs::write(dir.join("readme.txt"), "").unwrap();
+
+        let mut entries = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let exts: HashSet<String> = [".exe".to_string(), ".bat".to_string()].into_iter().collect();
+        scan_directory_with_extensions(&dir, &exts, false, &ShowAll, &mut entries, &mut seen);
+
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert!(names.contains(&"app"));
+        assert!(names.contains(&"script"));
+        assert!(!names.contains(&"readme"));
+        assert!(entries.iter().all(|e| !e.is_folder));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_with_extensions_includes_folders() {
+        let dir = temp_dir("ext_folders");
+        fs::write(dir.join("app.exe"), "").unwrap();
+        fs::create_dir(dir.join("subdir")).unwrap();
+
+        let mut entries = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let exts: HashSet<String> = [".exe".to_string()].into_iter().collect();
+        scan_directory_with_extensions(&dir, &exts, true, &ShowAll, &mut entries, &mut seen);
+
+        let folder_entries: Vec<&AppEntry> = entries.iter().filter(|e| e.is_folder).collect();
+        assert_eq!(folder_entries.len(), 1);
+        assert_eq!(folder_entries[0].name, "subdir");
+        assert!(folder_entries[0].is_folder);
+
+        let file_entries: Vec<&AppEntry> = entries.iter().filter(|e| !e.is_folder).collect();
+        assert_eq!(file_entries.len(), 1);
+        assert_eq!(file_entries[0].name, "app");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_with_extensions_no_folders_when_disabled() {
+        let dir = temp_dir("ext_no_folders");
+        fs::write(dir.join("app.exe"), "").unwrap();
+        fs::create_dir(dir.join("subdir")).unwrap();
+
+        let mut entries = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let exts: HashSet<String> = [".exe".to_string()].into_iter().collect();
+        scan_directory_with_extensions(&dir, &exts, false, &ShowAll, &mut entries, &mut seen);
+
+        assert!(entries.iter().all(|e| !e.is_folder));
+        assert_eq!(entries.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_keeps_same_name_different_paths() {
+        let dir = temp_dir("ext_dedup");
+        let sub1 = dir.join("a");
+        let sub2 = dir.join("b");
+        fs::create_dir_all(&sub1).unwrap();
+        fs::create_dir_all(&sub2).unwrap();
+        fs::write(sub1.join("tool.exe"), "").unwrap();
+        fs::write(sub2.join("tool.exe"), "").unwrap();
+
+        let mut entries = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let exts: HashSet<String> = [".exe".to_string()].into_iter().collect();
+        scan_directory_with_extensions(&dir, &exts, false, &ShowAll, &mut entries, &mut seen);
+
+        let tools: Vec<&AppEntry> = entries.iter().filter(|e| e.name == "tool").collect();
+        assert_eq!(tools.len(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_extensions_case_insensitive() {
+        let dir = temp_dir("ext_case");
+        fs::write(dir.join("app.EXE"), "").unwrap();
+
+        let mut entries = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let exts: HashSet<String> = [".exe".to_string()].into_iter().collect();
+        scan_directory_with_extensions(&dir, &exts, false, &ShowAll, &mut entries, &mut seen);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "app");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn index_cache_bincode_roundtrip() {
+        let mut snapshots = DirSnapshots::new();
+        snapshots.insert(
+            normalize_entry_key("C:\\apps"),
+            DirSnapshot {
+                modified: 42,
+                hidden: 1,
+                entries: vec![
+                    AppEntry {
+                        name: "Firefox".to_string(),
+                        target_path: "C:\\apps\\firefox.lnk".to_string(),
+                        is_folder: false,
+                        ..Default::default()
+                    },
+                    AppEntry {
+                        name: "Projects".to_string(),
+                        target_path: "C:\\apps\\Projects".to_string(),
+                        is_folder: true,
+                        ..Default::default()
+                    },
+                ],
+                subdirs: vec!["C:\\apps\\Projects".to_string()],
+            },
+        );
+
+        let cache = IndexCache {
+            built_at: 1700000000,
+            config_hash: 12345,
+            roots: vec![snapshots],
+            bookmarks: Vec::new(),
+            steam: None,
+        };
+
+        let paths = paths_config(&[scan_path(Path::new("C:\\apps"), &[".lnk"])], &[]);
+        let bytes = encode_cache(&cache, &paths).expect("serialize");
+        // Each entry is stored once, in the entry groups
+        let name = b"Firefox";
+        assert_eq!(bytes.windows(name.len()).filter(|w| w == name).count(), 1);
+        let file = CacheFile::parse(&bytes).expect("parse");
+        assert_eq!(file.config_hash, 12345);
+        let entries = file.entries(&paths).expect("entries");
+        assert!(entries_equal(&entries, &cache.entries(&paths)));
+        let restored = file.snapshots().expect("deserialize");
+
+        assert_eq!(restored.built_at, 1700000000);
+        assert_eq!(restored.config_hash, 12345);
+        let snapshot = &restored.roots[0][&normalize_entry_key("C:\\apps")];
+        assert_eq!(snapshot.modified, 42);
+        assert_eq!(snapshot.entries.len(), 2);
+        assert_eq!(snapshot.entries[0].name, "Firefox");
+        assert!(!snapshot.entries[0].is_folder);
+        assert_eq!(snapshot.entries[1].name, "Projects");
+        assert!(snapshot.entries[1].is_folder);
+        assert_eq!(snapshot.subdirs, vec!["C:\\apps\\Projects"]);
+    }
+
+    #[test]
+    fn cache_loads_only_for_matching_settings() {
+        let paths = paths_config(&[], &[]);
+        let cache = IndexCache::new(Vec::new(), Vec::new(), None, compute_config_hash(&paths, false));
+        let bytes = encode_cache(&cache, &paths).expect("serialize");
+
+        assert!(read_cache(&bytes, &paths, false).is_some());
+        assert!(read_cached_entries(&bytes, &paths, false).is_some());
+        // Other settings: the cache is stale
+        assert!(read_cache(&bytes, &paths, true).is_none());
+        assert!(read_cached_entries(&bytes, &paths, true).is_none());
+        // Truncated file: the snapshots do not decode
+        assert!(read_cache(&bytes[..bytes.len() / 2], &paths, false).is_none());
+    }
+
+    #[test]
+    fn cached_entries_match_flattened_snapshots() {
+        let dir = temp_dir("cache_groups");
+        let menu = dir.join("Menu");
+        let tools = menu.join("Tools");
+        let desktop = dir.join("Desktop");
+        fs::create_dir_all(&tools).unwrap();
+        fs::create_dir_all(&desktop).unwrap();
+        fs::write(menu.join("app.exe"), "").unwrap();
+        fs::write(tools.join("tool.exe"), "").unwrap();
+        fs::write(desktop.join("app.exe"), "").unwrap();
+        fs::write(desktop.join("notes.exe"), "").unwrap();
+        let bookmarks = dir.join("Bookmarks");
+        fs::write(&bookmarks, BOOKMARKS_JSON).unwrap();
+        let mut scan = vec![scan_path(&menu, &[".exe"]), scan_path(&desktop, &[".exe"])];
+        scan[1].preferred = true;
+        scan[1].weight = 2;
+        let paths = paths_config(&scan, &[&bookmarks]);
+
+        let (entries, cache, _) = scan_sources(&paths, true, None, &ScanMonitor::silent());
+        let bytes = encode_cache(&cache, &paths).expect("serialize");
+        let file = CacheFile::parse(&bytes).expect("parse");
+        assert!(entries_equal(&file.entries(&paths).expect("entries"), &entries));
+        let restored = file.snapshots().expect("snapshots");
+        assert!(entries_equal(&restored.entries(&paths), &entries));
+        assert_eq!(restored.roots[0].len(), 2);
+        assert_eq!(restored.bookmarks[0].entries.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn entry_kind_from_extension() {
+        assert_eq!(EntryKind::for_path("C:\\a\\Tool.LNK", false), EntryKind::AppShortcut);
+        assert_eq!(EntryKind::for_path("C:\\a\\site.url", false), EntryKind::Url);
+        assert_eq!(EntryKind::for_path("C:\\a\\setup.msi", false), EntryKind::Executable);
+        assert_eq!(EntryKind::for_path("C:\\a\\build.cmd", false), EntryKind::Command);
+        assert_eq!(EntryKind::for_path("C:\\a\\notes.txt", false), EntryKind::Document);
+        assert_eq!(EntryKind::for_path("C:\\a.d\\README", false), EntryKind::Document);
+        assert_eq!(EntryKind::for_path("C:\\a\\tools.exe", true), EntryKind::Folder);
+    }
+
+    #[test]
+    fn scan_records_kind_and_metadata() {
+        let dir = temp_dir("kind_metadata");
+        fs::write(dir.join("app.exe"), "12345").unwrap();
+        fs::write(dir.join("run.bat"), "").unwrap();
+        fs::create_dir_all(dir.join("Docs")).unwrap();
+        let sp = ScanPath {
+            include_folders: true,
+            ..scan_path(&dir, &[".exe", ".bat"])
+        };
+
+        let mut entries = full_scan(&[sp], true);
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        let kinds: Vec<(&str, EntryKind)> =
+            entries.iter().map(|e| (e.name.as_str(), e.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("Docs", EntryKind::Folder),
+                ("app", EntryKind::Executable),
+                ("run", EntryKind::Command),
+            ]
+        );
+        assert_eq!(entries[0].size, None);
+        assert_eq!(entries[1].size, Some(5));
+        assert!(entries.iter().all(|e| e.modified.is_some_and(|m| m > 0)));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn v1_cache_bytes(paths: &PathsConfig, entries: &[(&str, &str, bool)]) -> Vec<u8> {
+        let legacy = IndexCacheV1 {
+            built_at: 1600000000,
+            entries: entries
+                .iter()
+                .map(|(name, path, is_folder)| AppEntryV1 {
+                    name: name.to_string(),
+                    target_path: path.to_string(),
+                    is_folder: *is_folder,
+                })
+                .collect(),
+            config_hash: legacy_config_hash(paths, false).expect("v1 compatible"),
+        };
+        serialize_with_header(INDEX_MAGIC, INDEX_CACHE_VERSION_V1, &legacy).expect("serialize")
+    }
+
+    #[test]
+    fn v1_cache_is_migrated() {
+        let menu = Path::new("C:\\Menu");
+        let tools = Path::new("C:\\Tools");
+        let paths = paths_config(&[scan_path(menu, &[".lnk"]), scan_path(tools, &[".exe"])], &[]);
+        let bytes = v1_cache_bytes(
+            &paths,
+            &[
+                ("Editor", "C:\\Menu\\Editor.lnk", false),
+                ("Games", "C:\\Menu\\Games", true),
+                ("grep", "C:\\Tools\\bin\\grep.exe", false),
+                ("Stray", "C:\\Menuitems\\stray.lnk", false),
+            ],
+        );
+
+        let cache = read_cache(&bytes, &paths, false).expect("migrated");
+        assert_eq!(cache.built_at, 1600000000);
+        assert_eq!(cache.config_hash, compute_config_hash(&paths, false));
+        let entries = cache.entries(&paths);
+        let summary: Vec<(&str, EntryKind)> =
+            entries.iter().map(|e| (e.name.as_str(), e.kind)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Editor", EntryKind::AppShortcut),
+                ("Games", EntryKind::Folder),
+                ("grep", EntryKind::Executable),
+            ]
+        );
+        // Unstamped snapshots are re-read by the next rescan.
+        assert!(cache.roots.iter().flat_map(|r| r.values()).all(|s| s.modified == 0));
+    }
+
+    #[test]
+    fn v1_cache_for_other_config_is_discarded() {
+        let menu = Path::new("C:\\Menu");
+        let paths = paths_config(&[scan_path(menu, &[".lnk"])], &[]);
+        let bytes = v1_cache_bytes(&paths, &[("Editor", "C:\\Menu\\Editor.lnk", false)]);
+
+        let other = paths_config(&[scan_path(menu, &[".lnk", ".url"])], &[]);
+        assert!(read_cache(&bytes, &other, false).is_none());
+        let with_bookmarks = paths_config(&paths.scan, &[Path::new("C:\\Bookmarks")]);
+        assert!(read_cache(&bytes, &with_bookmarks, false).is_none());
+        assert!(read_cache(&bytes, &paths, true).is_none());
+    }
+
+    fn scan_path(dir: &Path, exts: &[&str]) -> ScanPath {
+        ScanPath {
+            path: dir.to_string_lossy().to_string(),
+            extensions: exts.iter().map(|e| e.to_string()).collect(),
+            include_folders: false,
+            ..Default::default()
+        }
+    }
+
+    fn snapshot_mut<'a>(roots: &'a mut [DirSnapshots], dir: &Path) -> &'a mut DirSnapshot {
+        roots[0]
+            .get_mut(&normalize_entry_key(&dir.to_string_lossy()))
+            .expect("snapshot")
+    }
+
+    #[test]
+    fn incremental_rescan_skips_unchanged_tree() {
+        let dir = temp_dir("incr_unchanged");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::write(dir.join("a").join("tool.exe"), "").unwrap();
+        fs::write(dir.join("top.exe"), "").unwrap();
+        let scan = vec![scan_path(&dir, &[".exe"])];
+
+        let (first, roots, report) = scan_incremental(&scan, true, None);
+        assert_eq!(first.len(), 2);
+        assert_eq!(report.reread.len(), 2);
+        assert!(report.skipped.is_empty());
+
+        let (second, _, report) = scan_incremental(&scan, true, Some(&roots));
+        assert!(entries_equal(&first, &second));
+        assert!(report.reread.is_empty());
+        assert_eq!(report.skipped, vec![dir.to_string_lossy().to_string()]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn incremental_rescan_rereads_only_changed_directory() {
+        let dir = temp_dir("incr_changed");
+        let a = dir.join("a");
+        let b = dir.join("b");
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        fs::write(a.join("one.exe"), "").unwrap();
+        fs::write(b.join("two.exe"), "").unwrap();
+        let scan = vec![scan_path(&dir, &[".exe"])];
+
+        let (_, mut roots, _) = scan_incremental(&scan, true, None);
+
+        // Add a file and make the cached fingerprint stale regardless of mtime resolution.
+        fs::write(a.join("three.exe"), "").unwrap();
+        snapshot_mut(&mut roots, &a).modified = 1;
+
+        let (entries, _, report) = scan_incremental(&scan, true, Some(&roots));
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert!(names.contains(&"three"));
+        assert_eq!(entries.len(), 3);
+        assert_eq!(report.reread, vec![a.to_string_lossy().to_string()]);
+        assert_eq!(report.skipped, vec![b.to_string_lossy().to_string()]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn incremental_rescan_reuses_stale_listing_when_fingerprint_matches() {
+        let dir = temp_dir("incr_reuse");
+        fs::write(dir.join("one.exe"), "").unwrap();
+        let scan = vec![scan_path(&dir, &[".exe"])];
+
+        let (_, mut roots, _) = scan_incremental(&scan, true, None);
+        snapshot_mut(&mut roots, &dir).entries.clear();
+
+        let (entries, _, report) = scan_incremental(&scan, true, Some(&roots));
+        assert!(entries.is_empty(), "matching fingerprint must not re-read the directory");
+        assert!(report.reread.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn incremental_rescan_zero_mtime_never_matches() {
+        let dir = temp_dir("incr_zero_mtime");
+        fs::write(dir.join("one.exe"), "").unwrap();
+        let scan = vec![scan_path(&dir, &[".exe"])];
+
+        let (_, mut roots, _) = scan_incremental(&scan, true, None);
+        snapshot_mut(&mut roots, &dir).modified = 0;
+
+        let (_, _, report) = scan_incremental(&scan, true, Some(&roots));
+        assert_eq!(report.reread.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn entry(name: &str, path: &str) -> AppEntry {
+        AppEntry {
+            name: name.into(),
+            target_path: path.into(),
+            is_folder: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diff_entries_detects_added_removed_changed() {
+        let old = vec![
+            entry("Keep", "C:\\keep.exe"),
+            entry("Gone", "C:\\gone.exe"),
+            entry("Old Name", "C:\\renamed.exe"),
+        ];
+        let new = vec![
+            entry("Keep", "C:\\keep.exe"),
+            entry("New Name", "C:\\renamed.exe"),
+            entry("Fresh", "C:\\fresh.exe"),
+        ];
+
+        let diff = diff_entries(&old, &new);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].name, "Fresh");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name, "Gone");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].name, "New Name");
+    }
+
+    #[test]
+    fn diff_entries_matches_paths_case_insensitively() {
+        let old = vec![entry("App", "C:\\Apps\\app.exe")];
+        let new = vec![entry("App", "c:/apps/APP.exe")];
+        let diff = diff_entries(&old, &new);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+    }
+
+    #[test]
+    fn apply_path_changes_picks_up_created_file() {
+        let dir = temp_dir("apply_create");
+        let sub = dir.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(sub.join("one.exe"), "").unwrap();
+        let scan = vec![scan_path(&dir, &[".exe"])];
+        let (_, mut roots, _) = scan_incremental(&scan, true, None);
+
+        let created = sub.join("two.exe");
+        fs::write(&created, "").unwrap();
+        let report = apply_path_changes(&scan, true, &mut roots, &[created]);
+
+        assert_eq!(report.reread, vec![sub.to_string_lossy().to_string()]);
+        let entries = flatten_snapshots(&scan, &roots, true);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert!(names.contains(&"one"));
+        assert!(names.contains(&"two"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn apply_path_changes_drops_removed_subtree() {
+        let dir = temp_dir("apply_remove_dir");
+        let sub = dir.join("sub");
+        let nested = sub.join("nested");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("deep.exe"), "").unwrap();
+        fs::write(dir.join("top.exe"), "").unwrap();
+        let scan = vec![scan_path(&dir, &[".exe"])];
+        let (_, mut roots, _) = scan_incremental(&scan, true, None);
+        assert_eq!(roots[0].len(), 3);
+
+        fs::remove_dir_all(&sub).unwrap();
+        apply_path_changes(&scan, true, &mut roots, std::slice::from_ref(&sub));
+
+        let entries = flatten_snapshots(&scan, &roots, true);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "top");
+        assert_eq!(roots[0].len(), 1, "snapshots of the removed subtree are dropped");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn apply_path_changes_ignores_unrelated_extensions_and_paths() {
+        let dir = temp_dir("apply_ignore");
+        fs::write(dir.join("one.exe"), "").unwrap();
+        let scan = vec![scan_path(&dir, &[".exe"])];
+        let (_, mut roots, _) = scan_incremental(&scan, true, None);
+
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        let outside = std::env::temp_dir().join("snotra_idx_test_elsewhere.exe");
+        let report =
+            apply_path_changes(&scan, true, &mut roots, &[dir.join("notes.txt"), outside]);
+        assert!(report.reread.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn apply_path_changes_handles_deleted_entry() {
+        let dir = temp_dir("apply_delete");
+        let file = dir.join("gone.exe");
+        fs::write(&file, "").unwrap();
+        fs::write(dir.join("kept.exe"), "").unwrap();
+        let scan = vec![scan_path(&dir, &[".exe"])];
+        let (_, mut roots, _) = scan_incremental(&scan, true, None);
+
+        fs::remove_file(&file).unwrap();
+        apply_path_changes(&scan, true, &mut roots, &[file]);
+
+        let entries = flatten_snapshots(&scan, &roots, true);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "kept");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_all_dedupes_overlapping_scan_paths() {
+        let dir = temp_dir("overlap");
+        let sub = dir.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(sub.join("tool.exe"), "").unwrap();
+
+        let scan = vec![scan_path(&dir, &[".exe"]), scan_path(&sub, &[".exe"])];
+        let entries = full_scan(&scan, true);
+        assert_eq!(entries.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn entries_carry_weight_of_their_scan_path() {
+        let dir = temp_dir("weight");
+        let sub = dir.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(dir.join("app.exe"), "").unwrap();
+        fs::write(sub.join("tool.exe"), "").unwrap();
+
+        let weighted = ScanPath {
+            weight: 3,
+            ..scan_path(&sub, &[".exe"])
+        };
+        let scan = vec![weighted, scan_path(&dir, &[".exe"])];
+        let mut entries = full_scan(&scan, true);
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        let weights: Vec<(&str, i32)> = entries.iter().map(|e| (e.name.as_str(), e.weight)).collect();
+        assert_eq!(weights, vec![("app", 0), ("tool", 3)]);
+
+        // Weights are applied when flattening, so changing them keeps the cache
+        let unweighted = [scan_path(&sub, &[".exe"]), scan_path(&dir, &[".exe"])];
+        assert_eq!(scan_hash(&scan), scan_hash(&unweighted));
+        let paths = paths_config(&unweighted, &[]);
+        let (_, roots, _) = scan_incremental(&scan, true, None);
+        let cache = IndexCache::new(roots, Vec::new(), None, scan_hash(&unweighted));
+        assert!(cache.entries(&paths).iter().all(|e| e.weight == 0));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_reads_shortcut_details() {
+        let dir = temp_dir("shortcut_details");
+        fs::write(
+            dir.join("Editor.lnk"),
+            include_bytes!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/lnk/unicode_full.lnk"
+            )),
+        )
+        .unwrap();
+        fs::write(dir.join("Broken.lnk"), b"not a shortcut").unwrap();
+
+        let mut entries = full_scan(&[scan_path(&dir, &[".lnk"])], true);
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "Broken");
+        assert!(entries[0].shortcut.is_none());
+        let shortcut = entries[1].shortcut.as_ref().expect("parsed shortcut");
+        assert_eq!(shortcut.target_stem(), Some("editor"));
+        assert_eq!(shortcut.description.as_deref(), Some("テキストエディター"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_reads_executable_version_info() {
+        let dir = temp_dir("version_info");
+        let winword = include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/pe/winword.exe"
+        ));
+        fs::write(dir.join("WINWORD.EXE"), winword).unwrap();
+        fs::write(dir.join("plain.exe"), b"MZ").unwrap();
+
+        let mut entries = full_scan(&[scan_path(&dir, &[".exe"])], true);
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(entries[0].name, "WINWORD");
+        assert_eq!(
+            entries[0].keywords,
+            vec!["Microsoft Office", "Microsoft Word", "Microsoft Corporation"]
+        );
+        assert!(entries[1].keywords.is_empty());
+
+        let target = ShortcutInfo {
+            target: Some(dir.join("WINWORD.EXE").to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let keywords =
+            version_keywords(&dir.join("Word.lnk"), ".lnk", "Microsoft Word", Some(&target));
+        assert_eq!(keywords, vec!["Microsoft Office", "Microsoft Corporation"]);
+        let missing = ShortcutInfo {
+            target: Some(dir.join("missing.exe").to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        assert!(version_keywords(&dir.join("x.lnk"), ".lnk", "x", Some(&missing)).is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn shortcuts_to_same_target_are_collapsed() {
+        let dir = temp_dir("dedup_targets");
+        let menu = dir.join("Menu");
+        let desktop = dir.join("Desktop");
+        let fixture = include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/lnk/unicode_full.lnk"
+        ));
+        for d in [&menu, &desktop] {
+            fs::create_dir_all(d).unwrap();
+            fs::write(d.join("Editor.lnk"), fixture).unwrap();
+        }
+        let menu_lnk = menu.join("Editor.lnk").to_string_lossy().to_string();
+        let desktop_lnk = desktop.join("Editor.lnk").to_string_lossy().to_string();
 
-fn normalize_entry_key(path: &str) -> String {
-    path.trim().replace('/', "\\").to_lowercase()
-}
+        let mut scan = vec![scan_path(&menu, &[".lnk"]), scan_path(&desktop, &[".lnk"])];
+        let entries = full_scan(&scan, true);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].target_path, menu_lnk);
+        assert_eq!(entries[0].aliases, vec![desktop_lnk.clone()]);
 
-#[derive(Serialize, Deserialize)]
-struct IndexCache {
-    built_at: u64,
-    entries: Vec<AppEntry>,
-    config_hash: u64,
-}
+        scan[1].preferred = true;
+        let entries = full_scan(&scan, true);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].target_path, desktop_lnk);
+        assert_eq!(entries[0].aliases, vec![menu_lnk]);
 
-fn compute_config_hash(scan: &[ScanPath], show_hidden_system: bool) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    for sp in scan {
-        sp.path.hash(&mut hasher);
-        sp.extensions.hash(&mut hasher);
-        sp.include_folders.hash(&mut hasher);
+        let entries = scan_all(&scan, true, false, &|_| {}, &CancelToken::new()).expect("scan");
+        assert_eq!(entries.len(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
     }
-    show_hidden_system.hash(&mut hasher);
-    hasher.finish()
-}
 
-fn cache_path() -> Option<PathBuf> {
-    Config::config_dir().map(|p| p.join("index.bin"))
-}
+    #[test]
+    fn dedup_keeps_different_arguments_and_can_be_disabled() {
+        let shortcut = |path: &str, arguments: Option<&str>| AppEntry {
+            name: "Browser".to_string(),
+            target_path: path.to_string(),
+            kind: EntryKind::AppShortcut,
+            shortcut: Some(ShortcutInfo {
+                target: Some("C:\\Apps\\browser.exe".to_string()),
+                arguments: arguments.map(str::to_string),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let entries = [
+            shortcut("C:\\Menu\\Browser.lnk", None),
+            shortcut("C:\\Desktop\\Browser.lnk", Some(" ")),
+            shortcut("C:\\Menu\\Browser (Private).lnk", Some("--private")),
+        ];
 
-fn icon_cache_path() -> Option<PathBuf> {
-    Config::config_dir().map(|p| p.join("icons.bin"))
-}
+        let mut dedup = EntryDeduper::new(true);
+        for entry in &entries {
+            dedup.push(entry);
+        }
+        let kept: Vec<&str> = dedup.entries.iter().map(|e| e.target_path.as_str()).collect();
+        assert_eq!(
+            kept,
+            vec!["C:\\Menu\\Browser.lnk", "C:\\Menu\\Browser (Private).lnk"]
+        );
+        assert_eq!(dedup.entries[0].aliases, vec!["C:\\Desktop\\Browser.lnk"]);
 
-fn invalidate_icon_cache_at(path: &Path) {
-    let _ = std::fs::remove_file(path);
-}
+        let mut dedup = EntryDeduper::new(false);
+        for entry in &entries {
+            dedup.push(entry);
+        }
+        assert_eq!(dedup.entries.len(), 3);
+        assert!(dedup.entries.iter().all(|e| e.aliases.is_empty()));
+    }
 
-fn invalidate_icon_cache() {
-    let Some(path) = icon_cache_path() else {
-        return;
-    };
-    invalidate_icon_cache_at(&path);
-}
+    #[test]
+    fn shortcut_without_arguments_collapses_with_its_executable() {
+        let exe = |path: &str| AppEntry {
+            name: "browser".to_string(),
+            target_path: path.to_string(),
+            kind: EntryKind::Executable,
+            ..Default::default()
+        };
+        let shortcut = |path: &str, target: &str| AppEntry {
+            name: "Browser".to_string(),
+            target_path: path.to_string(),
+            kind: EntryKind::AppShortcut,
+            shortcut: Some(ShortcutInfo {
+                target: Some(target.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let home = config::expand_path("~");
+        let home_exe = format!("{}\\Tools\\tool.exe", home);
+        let entries = [
+            exe("C:\\Apps\\browser.exe"),
+            shortcut("C:\\Menu\\Browser.lnk", "c:/apps/BROWSER.exe"),
+            exe(&home_exe),
+            shortcut("C:\\Menu\\Tool.lnk", "$HOME\\Tools\\tool.exe"),
+        ];
 
-/// Scan filesystem every startup; compare with cache to detect changes.
-/// Returns (entries, changed) where changed=true means the entry set differs from cache.
-pub fn load_or_scan(
-    scan: &[ScanPath],
-    show_hidden_system: bool,
-) -> (Vec<AppEntry>, bool) {
-    let current_hash = compute_config_hash(scan, show_hidden_system);
-
-    if let Some(cache) = load_cache(current_hash) {
-        let cached_entries = cache.entries;
-        let return_entries = cached_entries.clone();
-        spawn_background_rescan(
-            scan.to_vec(),
-            show_hidden_system,
-            current_hash,
-            cached_entries,
-        );
-        return (return_entries, false);
+        let mut dedup = EntryDeduper::new(true);
+        for entry in &entries {
+            dedup.push(entry);
+        }
+        let kept: Vec<&str> = dedup.entries.iter().map(|e| e.target_path.as_str()).collect();
+        assert_eq!(kept, vec!["C:\\Apps\\browser.exe", home_exe.as_str()]);
+        assert_eq!(dedup.entries[0].aliases, vec!["C:\\Menu\\Browser.lnk"]);
+        assert_eq!(dedup.entries[1].aliases, vec!["C:\\Menu\\Tool.lnk"]);
     }
 
-    let entries = scan_all(scan, show_hidden_system);
-    save_cache(&entries, current_hash);
-    (entries, true)
-}
+    #[test]
+    fn custom_entries_are_merged_before_scanned_entries() {
+        let custom = vec![
+            CustomEntry {
+                name: "Terminal".to_string(),
+                target: "wt.exe".to_string(),
+                args: "-p PowerShell".to_string(),
+                keywords: vec!["pwsh".to_string(), " ".to_string()],
+                ..Default::default()
+            },
+            CustomEntry {
+                name: "Portal".to_string(),
+                target: "https://portal.example.com/".to_string(),
+                ..Default::default()
+            },
+            CustomEntry {
+                name: "Notes".to_string(),
+                target: "C:\\Docs\\notes.txt".to_string(),
+                working_dir: "C:\\Docs".to_string(),
+                ..Default::default()
+            },
+            CustomEntry {
+                name: "Unnamed target".to_string(),
+                ..Default::default()
+            },
+        ];
+        let scanned = vec![
+            AppEntry {
+                name: "notes".to_string(),
+                target_path: "c:\\docs\\NOTES.txt".to_string(),
+                ..Default::default()
+            },
+            AppEntry {
+                name: "Editor".to_string(),
+                target_path: "C:\\Menu\\Editor.lnk".to_string(),
+                kind: EntryKind::AppShortcut,
+                ..Default::default()
+            },
+        ];
 
-fn entries_equal(a: &[AppEntry], b: &[AppEntry]) -> bool {
-    if a.len() != b.len() {
-        return false;
-    }
-    a.iter().zip(b.iter()).all(|(x, y)| {
-        x.name == y.name && x.target_path == y.target_path && x.is_folder == y.is_folder
-    })
-}
+        let merged = merge_custom_entries(scanned, &custom);
+        let summary: Vec<(&str, &str, EntryKind, bool)> = merged
+            .iter()
+            .map(|e| (e.name.as_str(), e.target_path.as_str(), e.kind, e.custom))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Terminal", "wt.exe -p PowerShell", EntryKind::Executable, true),
+                ("Portal", "https://portal.example.com/", EntryKind::Url, true),
+                ("Notes", "C:\\Docs\\notes.txt", EntryKind::Document, true),
+                ("Editor", "C:\\Menu\\Editor.lnk", EntryKind::AppShortcut, false),
+            ]
+        );
+        let terminal = merged[0].shortcut.as_ref().expect("launch info");
+        assert_eq!(terminal.target.as_deref(), Some("wt.exe"));
+        assert_eq!(terminal.arguments.as_deref(), Some("-p PowerShell"));
+        assert_eq!(merged[0].keywords, vec!["pwsh"]);
+        assert_eq!(merged[1].launch_target(), "https://portal.example.com/");
+        assert_eq!(
+            merged[2].shortcut.as_ref().and_then(|s| s.working_dir.as_deref()),
+            Some("C:\\Docs")
+        );
 
-fn save_cache(entries: &[AppEntry], config_hash: u64) {
-    let Some(path) = cache_path() else {
-        return;
-    };
-    if let Some(dir) = path.parent() {
-        let _ = std::fs::create_dir_all(dir);
+        // Merging again replaces the previous custom entries
+        let merged = merge_custom_entries(merged, &custom[1..2]);
+        let names: Vec<&str> = merged.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Portal", "Editor"]);
     }
 
-    let cache = IndexCache {
-        built_at: SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs(),
-        entries: entries.to_vec(),
-        config_hash,
-    };
-
-    let Some(bytes) = serialize_with_header(INDEX_MAGIC, INDEX_CACHE_VERSION, &cache) else {
-        return;
-    };
+    #[test]
+    fn scan_reads_internet_shortcuts() {
+        let dir = temp_dir("url_shortcut");
+        fs::write(
+            dir.join("GitHub.url"),
+            "[InternetShortcut]\r\nURL=https://github.com/\r\n",
+        )
+        .unwrap();
+        fs::write(dir.join("Empty.url"), "").unwrap();
+
+        let mut entries = full_scan(&[scan_path(&dir, &[".url"])], true);
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(entries.len(), 2);
+        // Unparseable files are still indexed, but open as a path.
+        assert!(entries[0].url.is_none());
+        assert_eq!(entries[0].launch_target(), entries[0].target_path);
+        assert_eq!(entries[1].name, "GitHub");
+        assert_eq!(entries[1].launch_target(), "https://github.com/");
 
-    let tmp_path = path.with_extension("bin.tmp");
-    if std::fs::write(&tmp_path, &bytes).is_ok() {
-        let _ = std::fs::remove_file(&path);
-        let _ = std::fs::rename(&tmp_path, &path);
+        let _ = fs::remove_dir_all(&dir);
     }
-}
 
-/// Force rebuild: scan and save cache, regardless of existing cache.
-/// Called from settings dialog (Phase 5).
-pub fn rebuild_and_save(scan: &[ScanPath], show_hidden_system: bool) -> Vec<AppEntry> {
-    let entries = scan_all(scan, show_hidden_system);
-    let config_hash = compute_config_hash(scan, show_hidden_system);
-    save_cache(&entries, config_hash);
-    entries
-}
+    #[test]
+    fn scan_skips_excluded_patterns() {
+        let dir = temp_dir("exclude");
+        for sub in ["node_modules/pkg", "target/debug", "build", "tools/build"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        fs::write(dir.join("node_modules/pkg/cli.exe"), "").unwrap();
+        fs::write(dir.join("target/debug/app.exe"), "").unwrap();
+        fs::write(dir.join("build/root.exe"), "").unwrap();
+        fs::write(dir.join("tools/build/nested.exe"), "").unwrap();
+        fs::write(dir.join("setup.tmp.exe"), "").unwrap();
+        fs::write(dir.join("keep.exe"), "").unwrap();
+
+        let mut sp = scan_path(&dir, &[".exe"]);
+        sp.exclude = vec![
+            "node_modules".to_string(),
+            "target/".to_string(),
+            "/build".to_string(),
+            "*.tmp.exe".to_string(),
+            "  ".to_string(),
+        ];
+        let mut names: Vec<String> = full_scan(&[sp], true).into_iter().map(|e| e.name).collect();
+        names.sort();
+        // `/build` is anchored to the scan root, so `tools/build` is still scanned.
+        assert_eq!(names, vec!["keep", "nested"]);
 
-fn load_cache(config_hash: u64) -> Option<IndexCache> {
-    let path = cache_path()?;
-    let bytes = std::fs::read(path).ok()?;
-    let cache: IndexCache = deserialize_with_header(&bytes, INDEX_MAGIC, INDEX_CACHE_VERSION)?;
-    if cache.config_hash != config_hash {
-        return None;
+        let _ = fs::remove_dir_all(&dir);
     }
-    Some(cache)
-}
 
-fn spawn_background_rescan(
-    scan: Vec<ScanPath>,
-    show_hidden_system: bool,
-    config_hash: u64,
-    cached_entries: Vec<AppEntry>,
-) {
-    let _ = thread::Builder::new()
-        .name("snotra-index-rescan".to_string())
-        .spawn(move || {
-            let scanned = scan_all(&scan, show_hidden_system);
-            if !entries_equal(&cached_entries, &scanned) {
-                save_cache(&scanned, config_hash);
-                invalidate_icon_cache();
-            }
-        });
-}
+    #[test]
+    fn scan_stops_at_max_depth() {
+        let dir = temp_dir("max_depth");
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        fs::write(dir.join("top.exe"), "").unwrap();
+        fs::write(dir.join("a/one.exe"), "").unwrap();
+        fs::write(dir.join("a/b/two.exe"), "").unwrap();
+
+        let names_at = |depth: Option<u32>| {
+            let mut sp = scan_path(&dir, &[".exe"]);
+            sp.include_folders = true;
+            sp.max_depth = depth;
+            let mut names: Vec<String> =
+                full_scan(&[sp], true).into_iter().map(|e| e.name).collect();
+            names.sort();
+            names
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
+        // Folders at the boundary are still listed, just not descended into.
+        assert_eq!(names_at(Some(0)), vec!["a", "top"]);
+        assert_eq!(names_at(Some(1)), vec!["a", "b", "one", "top"]);
+        assert_eq!(names_at(None), vec!["a", "b", "one", "top", "two"]);
 
-    fn temp_dir(tag: &str) -> std::path::PathBuf {
-        let dir = std::env::temp_dir().join(format!("snotra_idx_test_{}", tag));
         let _ = fs::remove_dir_all(&dir);
-        fs::create_dir_all(&dir).expect("create temp dir");
-        dir
     }
 
     #[test]
-    fn scan_with_extensions_filters_by_ext() {
-        let dir = temp_dir("ext_filter");
+    fn walk_collects_diagnostics() {
+        let dir = temp_dir("diagnostics");
+        let sub = dir.join("sub");
+        fs::create_dir_all(&sub).unwrap();
         fs::write(dir.join("app.exe"), "").unwrap();
-        fs::write(dir.join("script.bat"), "").unwrap();
-        fs::write(dir.join("readme.txt"), "").unwrap();
-
-        let mut entries = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-        let exts: HashSet<String> = [".exe".to_string(), ".bat".to_string()].into_iter().collect();
-        scan_directory_with_extensions(&dir, &exts, false, true, &mut entries, &mut seen);
+        fs::write(sub.join("tool.exe"), "").unwrap();
+        fs::write(sub.join(".secret.exe"), "").unwrap();
+        let monitor = ScanMonitor::silent();
+        let opts = ScanOptions::new(
+            &scan_path(&dir, &[".exe"]),
+            &crate::visibility::Dotfiles,
+            &monitor,
+        );
 
-        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
-        assert!(names.contains(&"app"));
-        assert!(names.contains(&"script"));
-        assert!(!names.contains(&"readme"));
-        assert!(entries.iter().all(|e| !e.is_folder));
+        let mut walk = WalkState::new(&dir.to_string_lossy());
+        let mut report = RescanReport::default();
+        let mut snapshots = DirSnapshots::new();
+        rescan_directory(&dir, &opts, None, &mut snapshots, &mut walk, &mut report);
+        assert_eq!(walk.stats.entries, 2);
+        assert_eq!(walk.stats.dirs_visited, 2);
+        assert_eq!(walk.stats.hidden_skipped, 1);
+        assert!(walk.stats.errors.is_empty());
+
+        // Reused snapshots are counted the same way.
+        let mut again = WalkState::new(&dir.to_string_lossy());
+        let previous = snapshots.clone();
+        rescan_directory(&dir, &opts, Some(&previous), &mut snapshots, &mut again, &mut report);
+        assert_eq!(again.stats, walk.stats);
 
         let _ = fs::remove_dir_all(&dir);
     }
 
     #[test]
-    fn scan_with_extensions_includes_folders() {
-        let dir = temp_dir("ext_folders");
-        fs::write(dir.join("app.exe"), "").unwrap();
-        fs::create_dir(dir.join("subdir")).unwrap();
+    fn scan_all_reports_progress_per_directory() {
+        let dir = temp_dir("progress");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("a").join("one.exe"), "").unwrap();
+        fs::write(dir.join("b").join("two.exe"), "").unwrap();
+        let seen = std::cell::RefCell::new(Vec::new());
+
+        let entries = scan_all(
+            &[scan_path(&dir, &[".exe"])],
+            true,
+            true,
+            &|p| seen.borrow_mut().push(p.clone()),
+            &CancelToken::new(),
+        )
+        .expect("not cancelled");
+
+        assert_eq!(entries.len(), 2);
+        let seen = seen.into_inner();
+        assert_eq!(seen.len(), 3);
+        assert_eq!(seen.iter().map(|p| p.dirs_visited).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(seen.last().unwrap().entries, 2);
+        // Each directory is reported once listed, before its subdirectories.
+        assert_eq!(seen[0].current_path, dir.to_string_lossy());
+        assert_eq!(seen[0].entries, 0);
 
-        let mut entries = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-        let exts: HashSet<String> = [".exe".to_string()].into_iter().collect();
-        scan_directory_with_extensions(&dir, &exts, true, true, &mut entries, &mut seen);
+        let _ = fs::remove_dir_all(&dir);
+    }
 
-        let folder_entries: Vec<&AppEntry> = entries.iter().filter(|e| e.is_folder).collect();
-        assert_eq!(folder_entries.len(), 1);
-        assert_eq!(folder_entries[0].name, "subdir");
-        assert!(folder_entries[0].is_folder);
+    #[test]
+    fn cancelled_scan_stops_and_returns_none() {
+        let dir = temp_dir("cancel");
+        for i in 0..5 {
+            let sub = dir.join(format!("d{}", i));
+            fs::create_dir_all(&sub).unwrap();
+            fs::write(sub.join("app.exe"), "").unwrap();
+        }
+        let cancel = CancelToken::new();
+        let calls = Cell::new(0);
+
+        let result = scan_all(
+            &[scan_path(&dir, &[".exe"])],
+            true,
+            true,
+            &|_| {
+                calls.set(calls.get() + 1);
+                cancel.cancel();
+            },
+            &cancel,
+        );
 
-        let file_entries: Vec<&AppEntry> = entries.iter().filter(|e| !e.is_folder).collect();
-        assert_eq!(file_entries.len(), 1);
-        assert_eq!(file_entries[0].name, "app");
+        assert!(result.is_none());
+        assert_eq!(calls.get(), 1);
 
         let _ = fs::remove_dir_all(&dir);
     }
 
     #[test]
-    fn scan_with_extensions_no_folders_when_disabled() {
-        let dir = temp_dir("ext_no_folders");
-        fs::write(dir.join("app.exe"), "").unwrap();
-        fs::create_dir(dir.join("subdir")).unwrap();
-
-        let mut entries = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-        let exts: HashSet<String> = [".exe".to_string()].into_iter().collect();
-        scan_directory_with_extensions(&dir, &exts, false, true, &mut entries, &mut seen);
+    fn unreadable_root_is_reported_as_error() {
+        let missing = std::env::temp_dir().join("snotra_idx_test_missing_root");
+        let _ = fs::remove_dir_all(&missing);
+        let (_, _, report) = scan_incremental(&[scan_path(&missing, &[".exe"])], true, None);
+
+        assert_eq!(report.diagnostics.len(), 1);
+        let stats = &report.diagnostics[0];
+        assert_eq!(stats.path, missing.to_string_lossy());
+        assert_eq!(stats.dirs_visited, 0);
+        assert_eq!(stats.errors.len(), 1);
+        assert_eq!(stats.errors[0].path, missing.to_string_lossy());
+        assert!(!stats.errors[0].permission_denied);
+    }
 
-        assert!(entries.iter().all(|e| !e.is_folder));
-        assert_eq!(entries.len(), 1);
+    /// Create a directory symlink; false where the platform refuses (e.g. no privilege).
+    fn link_dir(target: &Path, link: &Path) -> bool {
+        #[cfg(unix)]
+        let result = std::os::unix::fs::symlink(target, link);
+        #[cfg(windows)]
+        let result = std::os::windows::fs::symlink_dir(target, link);
+        result.is_ok()
+    }
 
-        let _ = fs::remove_dir_all(&dir);
+    fn sorted_names(entries: &[AppEntry]) -> Vec<&str> {
+        let mut names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        names.sort();
+        names
     }
 
     #[test]
-    fn scan_keeps_same_name_different_paths() {
-        let dir = temp_dir("ext_dedup");
-        let sub1 = dir.join("a");
-        let sub2 = dir.join("b");
-        fs::create_dir_all(&sub1).unwrap();
-        fs::create_dir_all(&sub2).unwrap();
-        fs::write(sub1.join("tool.exe"), "").unwrap();
-        fs::write(sub2.join("tool.exe"), "").unwrap();
+    fn scan_stops_at_link_to_ancestor() {
+        let dir = temp_dir("link_ancestor");
+        let sub = dir.join("a");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(sub.join("app.exe"), "").unwrap();
+        if !link_dir(&dir, &sub.join("loop")) {
+            return;
+        }
+        let sp = ScanPath {
+            include_folders: true,
+            ..scan_path(&dir, &[".exe"])
+        };
 
-        let mut entries = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-        let exts: HashSet<String> = [".exe".to_string()].into_iter().collect();
-        scan_directory_with_extensions(&dir, &exts, false, true, &mut entries, &mut seen);
+        let entries = full_scan(std::slice::from_ref(&sp), true);
+        assert_eq!(sorted_names(&entries), vec!["a", "app", "loop"]);
 
-        let tools: Vec<&AppEntry> = entries.iter().filter(|e| e.name == "tool").collect();
-        assert_eq!(tools.len(), 2);
+        // Incremental rescans and watcher refreshes terminate as well.
+        let scan = vec![sp];
+        let (_, mut roots, _) = scan_incremental(&scan, true, None);
+        let (again, _, _) = scan_incremental(&scan, true, Some(&roots));
+        assert_eq!(sorted_names(&again), vec!["a", "app", "loop"]);
+        let created = sub.join("new.exe");
+        fs::write(&created, "").unwrap();
+        apply_path_changes(&scan, true, &mut roots, &[created]);
+        let entries = flatten_snapshots(&scan, &roots, true);
+        assert_eq!(sorted_names(&entries), vec!["a", "app", "loop", "new"]);
 
         let _ = fs::remove_dir_all(&dir);
     }
 
     #[test]
-    fn scan_extensions_case_insensitive() {
-        let dir = temp_dir("ext_case");
-        fs::write(dir.join("app.EXE"), "").unwrap();
-
-        let mut entries = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-        let exts: HashSet<String> = [".exe".to_string()].into_iter().collect();
-        scan_directory_with_extensions(&dir, &exts, false, true, &mut entries, &mut seen);
+    fn scan_visits_mutually_linked_directories_once() {
+        let dir = temp_dir("link_mutual");
+        let x = dir.join("x");
+        let y = dir.join("y");
+        fs::create_dir_all(&x).unwrap();
+        fs::create_dir_all(&y).unwrap();
+        fs::write(x.join("one.exe"), "").unwrap();
+        fs::write(y.join("two.exe"), "").unwrap();
+        if !link_dir(&y, &x.join("to_y")) || !link_dir(&x, &y.join("to_x")) {
+            return;
+        }
 
-        assert_eq!(entries.len(), 1);
-        assert_eq!(entries[0].name, "app");
+        let entries = full_scan(&[scan_path(&dir, &[".exe"])], true);
+        assert_eq!(sorted_names(&entries), vec!["one", "two"]);
 
         let _ = fs::remove_dir_all(&dir);
     }
 
     #[test]
-    fn index_cache_bincode_roundtrip() {
-        let entries = vec![
-            AppEntry {
-                name: "Firefox".to_string(),
-                target_path: "C:\\apps\\firefox.lnk".to_string(),
-                is_folder: false,
-            },
-            AppEntry {
-                name: "Projects".to_string(),
-                target_path: "C:\\Projects".to_string(),
-                is_folder: true,
-            },
-        ];
-
-        let cache = IndexCache {
-            built_at: 1700000000,
-            entries: entries.clone(),
-            config_hash: 12345,
+    fn follow_links_disabled_lists_but_skips_linked_folders() {
+        let dir = temp_dir("link_nofollow");
+        let outside = temp_dir("link_nofollow_target");
+        fs::write(outside.join("tool.exe"), "").unwrap();
+        if !link_dir(&outside, &dir.join("Tools")) {
+            return;
+        }
+        let follow = ScanPath {
+            include_folders: true,
+            ..scan_path(&dir, &[".exe"])
+        };
+        let no_follow = ScanPath {
+            follow_links: false,
+            ..follow.clone()
         };
 
-        let bytes =
-            serialize_with_header(INDEX_MAGIC, INDEX_CACHE_VERSION, &cache).expect("serialize");
-        let restored: IndexCache =
-            deserialize_with_header(&bytes, INDEX_MAGIC, INDEX_CACHE_VERSION).expect("deserialize");
+        let entries = full_scan(std::slice::from_ref(&follow), true);
+        assert_eq!(sorted_names(&entries), vec!["Tools", "tool"]);
+        let entries = full_scan(std::slice::from_ref(&no_follow), true);
+        assert_eq!(sorted_names(&entries), vec!["Tools"]);
+        assert_ne!(scan_hash(&[follow]), scan_hash(&[no_follow]));
 
-        assert_eq!(restored.built_at, 1700000000);
-        assert_eq!(restored.entries.len(), 2);
-        assert_eq!(restored.entries[0].name, "Firefox");
-        assert!(!restored.entries[0].is_folder);
-        assert_eq!(restored.entries[1].name, "Projects");
-        assert!(restored.entries[1].is_folder);
-        assert_eq!(restored.config_hash, 12345);
+        let _ = fs::remove_dir_all(&dir);
+        let _ = fs::remove_dir_all(&outside);
     }
 
     #[test]
@@ -409,14 +2868,16 @@ mod tests {
             path: "C:\\A".to_string(),
             extensions: vec![".lnk".to_string()],
             include_folders: false,
+            ..Default::default()
         }];
         let scan2 = vec![ScanPath {
             path: "C:\\B".to_string(),
             extensions: vec![".lnk".to_string()],
             include_folders: false,
+            ..Default::default()
         }];
-        let hash1 = compute_config_hash(&scan1, false);
-        let hash2 = compute_config_hash(&scan2, false);
+        let hash1 = scan_hash(&scan1);
+        let hash2 = scan_hash(&scan2);
         assert_ne!(hash1, hash2);
     }
 
@@ -427,11 +2888,13 @@ mod tests {
                 name: "A".into(),
                 target_path: "C:\\a.exe".into(),
                 is_folder: false,
+                ..Default::default()
             },
             AppEntry {
                 name: "B".into(),
                 target_path: "C:\\b".into(),
                 is_folder: true,
+                ..Default::default()
             },
         ];
         let b = a.clone();
@@ -444,17 +2907,20 @@ mod tests {
             name: "A".into(),
             target_path: "C:\\a.exe".into(),
             is_folder: false,
+            ..Default::default()
         }];
         let b = vec![
             AppEntry {
                 name: "A".into(),
                 target_path: "C:\\a.exe".into(),
                 is_folder: false,
+                ..Default::default()
             },
             AppEntry {
                 name: "B".into(),
                 target_path: "C:\\b.exe".into(),
                 is_folder: false,
+                ..Default::default()
             },
         ];
         assert!(!entries_equal(&a, &b));
@@ -466,11 +2932,13 @@ mod tests {
             name: "A".into(),
             target_path: "C:\\a.exe".into(),
             is_folder: false,
+            ..Default::default()
         }];
         let b = vec![AppEntry {
             name: "B".into(),
             target_path: "C:\\a.exe".into(),
             is_folder: false,
+            ..Default::default()
         }];
         assert!(!entries_equal(&a, &b));
     }
@@ -481,11 +2949,13 @@ mod tests {
             name: "A".into(),
             target_path: "C:\\a.exe".into(),
             is_folder: false,
+            ..Default::default()
         }];
         let b = vec![AppEntry {
             name: "A".into(),
             target_path: "C:\\b.exe".into(),
             is_folder: false,
+            ..Default::default()
         }];
         assert!(!entries_equal(&a, &b));
     }
@@ -496,11 +2966,13 @@ mod tests {
             name: "A".into(),
             target_path: "C:\\a".into(),
             is_folder: false,
+            ..Default::default()
         }];
         let b = vec![AppEntry {
             name: "A".into(),
             target_path: "C:\\a".into(),
             is_folder: true,
+            ..Default::default()
         }];
         assert!(!entries_equal(&a, &b));
     }
@@ -516,17 +2988,180 @@ mod tests {
             path: "C:\\Tools".to_string(),
             extensions: vec![".exe".to_string()],
             include_folders: false,
+            ..Default::default()
         }];
         let scan2 = vec![ScanPath {
             path: "C:\\Tools".to_string(),
             extensions: vec![".exe".to_string(), ".bat".to_string()],
             include_folders: false,
+            ..Default::default()
         }];
-        let hash1 = compute_config_hash(&scan1, false);
-        let hash2 = compute_config_hash(&scan2, false);
+        let hash1 = scan_hash(&scan1);
+        let hash2 = scan_hash(&scan2);
         assert_ne!(hash1, hash2);
     }
 
+    #[test]
+    fn config_hash_changes_with_exclude_and_depth() {
+        let base = vec![ScanPath {
+            path: "C:\\Tools".to_string(),
+            extensions: vec![".exe".to_string()],
+            ..Default::default()
+        }];
+        let mut excluded = base.clone();
+        excluded[0].exclude = vec!["node_modules".to_string()];
+        let mut limited = base.clone();
+        limited[0].max_depth = Some(2);
+
+        let hash = scan_hash(&base);
+        assert_ne!(hash, scan_hash(&excluded));
+        assert_ne!(hash, scan_hash(&limited));
+    }
+
+    #[test]
+    fn config_hash_changes_with_bookmark_sources() {
+        let scan = [scan_path(Path::new("C:\\Tools"), &[".exe"])];
+        let without = compute_config_hash(&paths_config(&scan, &[]), false);
+        let with = compute_config_hash(
+            &paths_config(&scan, &[Path::new("C:\\Chrome\\Bookmarks")]),
+            false,
+        );
+        assert_ne!(without, with);
+    }
+
+    const BOOKMARKS_JSON: &str = r#"{"roots": {"bookmark_bar": {"type": "folder", "name": "Bar",
+        "children": [{"type": "url", "name": "Docs", "url": "https://docs.rs/"}]}}}"#;
+
+    #[test]
+    fn index_json_roundtrip_requires_matching_settings() {
+        let dir = temp_dir("index_json");
+        fs::write(dir.join("app.exe"), "").unwrap();
+        fs::write(dir.join("tool.bat"), "").unwrap();
+        let paths = paths_config(&[scan_path(&dir, &[".exe", ".bat"])], &[]);
+        let (entries, cache, _) = scan_sources(&paths, false, None, &ScanMonitor::silent());
+
+        let json = serde_json::to_string_pretty(&cache).expect("serialize");
+        let restored = parse_index_json(&json, &paths, false).expect("valid");
+        assert!(entries_equal(&restored.entries(&paths), &entries));
+
+        assert!(matches!(
+            parse_index_json(&json, &paths, true),
+            Err(ImportError::Invalid(_))
+        ));
+        let other = paths_config(&[scan_path(&dir, &[".exe"])], &[]);
+        assert!(matches!(
+            parse_index_json(&json, &other, false),
+            Err(ImportError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse_index_json("{}", &paths, false),
+            Err(ImportError::Parse(_))
+        ));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_sources_merges_bookmarks_and_reuses_unchanged_file() {
+        let dir = temp_dir("bookmark_sources");
+        fs::write(dir.join("app.exe"), "").unwrap();
+        let file = dir.join("Bookmarks");
+        fs::write(&file, BOOKMARKS_JSON).unwrap();
+        let missing = dir.join("missing").join("Bookmarks");
+        let paths = paths_config(&[scan_path(&dir, &[".exe"])], &[&file, &missing]);
+
+        let (entries, cache, report) = scan_sources(&paths, true, None, &ScanMonitor::silent());
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["app", "Docs"]);
+        assert_eq!(entries[1].context.as_deref(), Some("Bar"));
+        assert!(report.reread.contains(&file.to_string_lossy().to_string()));
+
+        let (again, _, report) = scan_sources(&paths, true, Some(&cache), &ScanMonitor::silent());
+        assert_eq!(again.len(), 2);
+        assert!(report.skipped.contains(&file.to_string_lossy().to_string()));
+        // A missing file has no fingerprint and is always retried.
+        assert!(report.reread.contains(&missing.to_string_lossy().to_string()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn cancelled_scan_sources_skips_bookmarks() {
+        let dir = temp_dir("bookmark_cancel");
+        let file = dir.join("Bookmarks");
+        fs::write(&file, BOOKMARKS_JSON).unwrap();
+        let paths = paths_config(&[scan_path(&dir, &[".exe"])], &[&file]);
+        let cancel = CancelToken::new();
+        cancel.cancel();
+
+        let monitor = ScanMonitor::new(&|_| {}, Some(&cancel));
+        let (entries, cache, report) = scan_sources(&paths, true, None, &monitor);
+        assert!(entries.is_empty());
+        assert!(cache.bookmarks.is_empty());
+        assert!(!report.reread.contains(&file.to_string_lossy().to_string()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_sources_indexes_steam_apps_and_reuses_unchanged_libraries() {
+        let dir = temp_dir("steam_source");
+        let steamapps = dir.join("steamapps");
+        fs::create_dir_all(&steamapps).unwrap();
+        fs::write(
+            steamapps.join("appmanifest_440.acf"),
+            "\"AppState\" { \"appid\" \"440\" \"name\" \"Team Fortress 2\" \"StateFlags\" \"4\" }",
+        )
+        .unwrap();
+        fs::write(steamapps.join("libraryfolders.vdf"), "\"libraryfolders\" {}").unwrap();
+        let steam_dir = dir.to_string_lossy().to_string();
+        let paths = PathsConfig {
+            steam_dir: Some(steam_dir.clone()),
+            ..paths_config(&[], &[])
+        };
+        assert_ne!(
+            compute_config_hash(&paths, false),
+            compute_config_hash(&paths_config(&[], &[]), false)
+        );
+
+        let (entries, cache, report) = scan_sources(&paths, false, None, &ScanMonitor::silent());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "Team Fortress 2");
+        assert_eq!(entries[0].launch_target(), "steam://rungameid/440");
+        assert!(report.reread.contains(&steam_dir));
+
+        let (again, _, report) = scan_sources(&paths, false, Some(&cache), &ScanMonitor::silent());
+        assert!(entries_equal(&again, &entries));
+        assert!(report.skipped.contains(&steam_dir));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn apply_bookmark_changes_rereads_named_file_only() {
+        let dir = temp_dir("bookmark_changes");
+        let file = dir.join("Bookmarks");
+        fs::write(&file, BOOKMARKS_JSON).unwrap();
+        let paths = paths_config(&[], &[&file]);
+        let (_, mut cache, _) = scan_sources(&paths, true, None, &ScanMonitor::silent());
+
+        fs::write(&file, BOOKMARKS_JSON.replace("Docs", "Crates")).unwrap();
+        let mut report = RescanReport::default();
+        apply_bookmark_changes(
+            &paths.bookmarks,
+            &mut cache.bookmarks,
+            &[dir.join("Bookmarks.tmp")],
+            &mut report,
+        );
+        assert!(report.reread.is_empty());
+
+        apply_bookmark_changes(&paths.bookmarks, &mut cache.bookmarks, &[file], &mut report);
+        assert_eq!(report.reread.len(), 1);
+        assert_eq!(cache.entries(&paths)[0].name, "Crates");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn invalidate_icon_cache_removes_icons_bin_if_present() {
         let dir = temp_dir("icons_cache_remove");
@@ -554,7 +3189,7 @@ mod tests {
 
     #[test]
     fn scan_all_empty_when_no_paths() {
-        let entries = scan_all(&[], false);
+        let entries = full_scan(&[], false);
         assert!(entries.is_empty(), "scan_all with no paths should return empty");
     }
 }
@@ -1,9 +1,19 @@
+mod arena;
 pub mod binfmt;
+pub mod bookmarks;
 pub mod config;
+pub mod diagnostics;
+pub mod export;
 pub mod folder;
 pub mod history;
 pub mod indexer;
 pub mod query;
+mod romaji;
 pub mod search;
+pub mod shortcut;
+pub mod steam;
 pub mod ui_types;
+pub mod version_info;
+pub mod visibility;
+pub mod watcher;
 pub mod window_data;
@@ -1,25 +1,86 @@
+use std::ops::Range;
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+/// Normalize a search query: trim, collapse whitespace runs to one space and apply
+/// `fold_text`. Used for queries, folder filters and history query keys.
 pub fn normalize_query(query: &str) -> String {
     let mut out = String::new();
     let mut prev_space = false;
 
-    for ch in query.trim().chars() {
+    for ch in fold_text(query.trim()).chars() {
         if ch.is_whitespace() {
             if !prev_space {
                 out.push(' ');
                 prev_space = true;
             }
         } else {
-            out.extend(ch.to_lowercase());
+            out.push(ch);
             prev_space = false;
         }
     }
 
+    out.truncate(out.trim_end().len());
     out
 }
 
+/// Fold spelling variants that should match each other: NFKC compatibility forms
+/// (full-width `ｆｉｒｅｆｏｘ`, half-width `ﾒﾓ`), case, Latin diacritics (`café` → `cafe`)
+/// and katakana to hiragana (`メモ` → `めも`). Voiced kana keep their dakuten.
+pub fn fold_text(text: &str) -> String {
+    let stripped: String = text.nfkd().filter(|&c| !is_latin_diacritic(c)).collect();
+    let mut out = String::with_capacity(stripped.len());
+    for ch in stripped.nfc().flat_map(char::to_lowercase) {
+        out.push(katakana_to_hiragana(ch));
+    }
+    out
+}
+
+/// `fold_text`, also giving for each char of the result the range of chars of `text` it
+/// came from, so matches on the folded text can be shown on the original. A base char and
+/// the marks following it (`ｶﾞ`, `e` + U+0301) fold together.
+pub fn fold_text_with_spans(text: &str) -> (String, Vec<Range<usize>>) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut folded = String::with_capacity(text.len());
+    let mut spans = Vec::with_capacity(chars.len());
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start + 1;
+        while end < chars.len() && joins_previous(chars[end]) {
+            end += 1;
+        }
+        let group: String = chars[start..end].iter().collect();
+        for c in fold_text(&group).chars() {
+            folded.push(c);
+            spans.push(start..end);
+        }
+        start = end;
+    }
+    (folded, spans)
+}
+
+/// Marks that normalization may combine with the char before them; half-width voiced
+/// marks become combining marks under NFKC.
+fn joins_previous(c: char) -> bool {
+    is_combining_mark(c) || matches!(c, 'ﾞ' | 'ﾟ')
+}
+
+/// Combining Diacritical Marks block; kana (semi-)voiced sound marks lie outside it.
+fn is_latin_diacritic(c: char) -> bool {
+    ('\u{0300}'..='\u{036F}').contains(&c)
+}
+
+fn katakana_to_hiragana(c: char) -> char {
+    match c {
+        'ァ'..='ヶ' | 'ヽ'..='ヾ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::normalize_query;
+    use super::{fold_text, fold_text_with_spans, normalize_query};
 
     #[test]
     fn trim_and_lowercase() {
@@ -30,4 +91,48 @@ mod tests {
     fn collapse_whitespace() {
         assert_eq!(normalize_query("a   b\t\tc"), "a b c");
     }
+
+    #[test]
+    fn folds_width_variants() {
+        assert_eq!(normalize_query("ｆｉｒｅｆｏｘ"), "firefox");
+        assert_eq!(normalize_query("ﾒﾓ帳"), normalize_query("メモ帳"));
+        assert_eq!(normalize_query("ｶﾞｲﾄﾞ"), "がいど");
+        assert_eq!(normalize_query("a\u{3000}b"), "a b");
+    }
+
+    #[test]
+    fn folds_katakana_to_hiragana() {
+        assert_eq!(normalize_query("メモ帳"), "めも帳");
+        assert_eq!(normalize_query("ヴァイオリン"), normalize_query("ゔぁいおりん"));
+        // Voiced marks are kept.
+        assert_ne!(fold_text("ガ"), fold_text("カ"));
+    }
+
+    #[test]
+    fn folds_latin_diacritics() {
+        assert_eq!(normalize_query("Café Crème"), "cafe creme");
+        assert_eq!(fold_text("Ångström"), "angstrom");
+    }
+
+    #[test]
+    fn fold_spans_point_back_to_original_chars() {
+        let (folded, spans) = fold_text_with_spans("ｶﾞｲﾄﾞ Café");
+        assert_eq!(folded, fold_text("ｶﾞｲﾄﾞ Café"));
+        assert_eq!(folded, "がいど cafe");
+        assert_eq!(spans[0], 0..2);
+        assert_eq!(spans[1], 2..3);
+        assert_eq!(spans[2], 3..5);
+        assert_eq!(spans[7], 9..10);
+        let (folded, spans) = fold_text_with_spans("ﬁle");
+        assert_eq!(folded, "file");
+        assert_eq!(spans[..2], [0..1, 0..1]);
+    }
+
+    #[test]
+    fn normalization_is_idempotent() {
+        for q in ["ﾒﾓ帳", "Café", "ＰｏｗｅｒＳｈｅｌｌ", "  ガイド  ｸﾞﾗﾌ "] {
+            let once = normalize_query(q);
+            assert_eq!(normalize_query(&once), once);
+        }
+    }
 }
@@ -0,0 +1,295 @@
+//! Romaji transliteration of kana, so names like `メモ帳` can be found with the IME off.
+//!
+//! Kana is written in Hepburn and then reduced by `canonical_romaji`, which also maps
+//! common IME spellings (`si`/`shi`, `tu`/`tsu`, `nn`/`n`, ...) to one form. Queries go
+//! through the same reduction, so either spelling matches. Kanji have no reading here and
+//! are kept as they are.
+
+/// Canonical romaji of a `fold_text`-folded name, or `None` when it contains no kana.
+pub(crate) fn romanize(folded: &str) -> Option<String> {
+    if !folded.chars().any(is_kana) {
+        return None;
+    }
+    let mut out = String::with_capacity(folded.len() * 2);
+    let mut sokuon = false;
+    for c in folded.chars() {
+        if let Some(vowel) = small_vowel(c) {
+            // ふぁ -> fa, てぃ -> ti: the small vowel replaces the previous one.
+            if out.ends_with(['a', 'i', 'u', 'e', 'o']) {
+                out.pop();
+            }
+            out.push(vowel);
+            continue;
+        }
+        if let Some(vowel) = small_y(c) {
+            // きゃ -> kya, しゃ -> sha, じゃ -> ja.
+            if out.ends_with('i') {
+                out.pop();
+                if !(out.ends_with("sh") || out.ends_with("ch") || out.ends_with('j')) {
+                    out.push('y');
+                }
+            } else {
+                out.push('y');
+            }
+            out.push(vowel);
+            continue;
+        }
+        if c == 'っ' {
+            sokuon = true;
+            continue;
+        }
+        let syllable = match c {
+            'ー' => "-",
+            _ => kana_syllable(c).unwrap_or(""),
+        };
+        if sokuon {
+            // っか -> kka, っち -> tchi.
+            match syllable.chars().next() {
+                Some('c') => out.push('t'),
+                Some(first) if !"aiueon-".contains(first) => out.push(first),
+                _ => {}
+            }
+            sokuon = false;
+        }
+        if syllable.is_empty() {
+            out.push(c);
+        } else {
+            out.push_str(syllable);
+        }
+    }
+    Some(canonical_romaji(&out))
+}
+
+/// Reduce romaji spelling variants to one form: `shi`/`si` -> `si`, `chi`/`ti` -> `ti`,
+/// `tsu` -> `tu`, `fu` -> `hu`, `ji`/`di` -> `zi`, `du` -> `zu`, `sha` -> `sya`,
+/// `cha` -> `tya`, `ja`/`jya` -> `zya`, `wo` -> `o`, `ca`/`cu`/`co` -> `k`, runs of `n`
+/// to one `n`; long-vowel marks and apostrophes are dropped. Other text is kept.
+pub(crate) fn canonical_romaji(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let at = |i: usize| chars.get(i).copied().unwrap_or('\0');
+    let is_vowel = |c: char| "aiueo".contains(c);
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match (c, at(i + 1), at(i + 2)) {
+            ('-' | '\'', ..) => i += 1,
+            ('t', 'c', 'h') => {
+                out.push('t');
+                i += 1;
+            }
+            ('s', 'h', v) | ('c', 'h', v) if is_vowel(v) => {
+                out.push(if c == 's' { 's' } else { 't' });
+                if v != 'i' {
+                    out.push('y');
+                }
+                out.push(v);
+                i += 3;
+            }
+            ('j', 'y', v) if is_vowel(v) => {
+                out.push_str("zy");
+                out.push(v);
+                i += 3;
+            }
+            ('j', v, _) if is_vowel(v) => {
+                out.push('z');
+                if v != 'i' {
+                    out.push('y');
+                }
+                out.push(v);
+                i += 2;
+            }
+            ('t', 's', 'u') => {
+                out.push_str("tu");
+                i += 3;
+            }
+            ('f', 'u', _) => {
+                out.push_str("hu");
+                i += 2;
+            }
+            ('d', v @ ('i' | 'u'), _) => {
+                out.push('z');
+                out.push(v);
+                i += 2;
+            }
+            ('w', 'o', _) => {
+                out.push('o');
+                i += 2;
+            }
+            ('c', v @ ('a' | 'u' | 'o'), _) => {
+                out.push('k');
+                out.push(v);
+                i += 2;
+            }
+            ('c', v @ ('i' | 'e'), _) => {
+                out.push('s');
+                out.push(v);
+                i += 2;
+            }
+            ('n', ..) => {
+                out.push('n');
+                while at(i + 1) == 'n' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn is_kana(c: char) -> bool {
+    ('ぁ'..='ゖ').contains(&c)
+}
+
+fn small_vowel(c: char) -> Option<char> {
+    match c {
+        'ぁ' => Some('a'),
+        'ぃ' => Some('i'),
+        'ぅ' => Some('u'),
+        'ぇ' => Some('e'),
+        'ぉ' => Some('o'),
+        _ => None,
+    }
+}
+
+fn small_y(c: char) -> Option<char> {
+    match c {
+        'ゃ' => Some('a'),
+        'ゅ' => Some('u'),
+        'ょ' => Some('o'),
+        _ => None,
+    }
+}
+
+/// Hepburn spelling of one full-size hiragana.
+fn kana_syllable(c: char) -> Option<&'static str> {
+    let s = match c {
+        'あ' => "a",
+        'い' => "i",
+        'う' => "u",
+        'え' => "e",
+        'お' => "o",
+        'か' | 'ゕ' => "ka",
+        'き' => "ki",
+        'く' => "ku",
+        'け' | 'ゖ' => "ke",
+        'こ' => "ko",
+        'が' => "ga",
+        'ぎ' => "gi",
+        'ぐ' => "gu",
+        'げ' => "ge",
+        'ご' => "go",
+        'さ' => "sa",
+        'し' => "shi",
+        'す' => "su",
+        'せ' => "se",
+        'そ' => "so",
+        'ざ' => "za",
+        'じ' => "ji",
+        'ず' => "zu",
+        'ぜ' => "ze",
+        'ぞ' => "zo",
+        'た' => "ta",
+        'ち' => "chi",
+        'つ' => "tsu",
+        'て' => "te",
+        'と' => "to",
+        'だ' => "da",
+        'ぢ' => "ji",
+        'づ' => "zu",
+        'で' => "de",
+        'ど' => "do",
+        'な' => "na",
+        'に' => "ni",
+        'ぬ' => "nu",
+        'ね' => "ne",
+        'の' => "no",
+        'は' => "ha",
+        'ひ' => "hi",
+        'ふ' => "fu",
+        'へ' => "he",
+        'ほ' => "ho",
+        'ば' => "ba",
+        'び' => "bi",
+        'ぶ' => "bu",
+        'べ' => "be",
+        'ぼ' => "bo",
+        'ぱ' => "pa",
+        'ぴ' => "pi",
+        'ぷ' => "pu",
+        'ぺ' => "pe",
+        'ぽ' => "po",
+        'ま' => "ma",
+        'み' => "mi",
+        'む' => "mu",
+        'め' => "me",
+        'も' => "mo",
+        'や' => "ya",
+        'ゆ' => "yu",
+        'よ' => "yo",
+        'ら' => "ra",
+        'り' => "ri",
+        'る' => "ru",
+        'れ' => "re",
+        'ろ' => "ro",
+        'わ' | 'ゎ' => "wa",
+        'ゐ' => "i",
+        'ゑ' => "e",
+        'を' => "wo",
+        'ん' => "n",
+        'ゔ' => "vu",
+        _ => return None,
+    };
+    Some(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn romanizes_kana_and_keeps_other_text() {
+        assert_eq!(romanize("めも帳").as_deref(), Some("memo帳"));
+        assert_eq!(romanize("ふぉるだ").as_deref(), Some("foruda"));
+        assert_eq!(romanize("ぺいんと 3d").as_deref(), Some("peinto 3d"));
+        assert_eq!(romanize("notepad"), None);
+        assert_eq!(romanize("画像"), None);
+    }
+
+    #[test]
+    fn romanizes_contracted_and_geminate_sounds() {
+        assert_eq!(romanize("きゃっしゅ").as_deref(), Some(canonical_romaji("kyasshu").as_str()));
+        assert_eq!(romanize("まっちゃ").as_deref(), Some(canonical_romaji("matcha").as_str()));
+        assert_eq!(romanize("じゃんぷ").as_deref(), Some("zyanpu"));
+        assert_eq!(romanize("こーひー").as_deref(), Some("kohi"));
+    }
+
+    #[test]
+    fn hepburn_and_ime_spellings_agree() {
+        for (a, b) in [
+            ("shashin", "syasin"),
+            ("chizu", "tizu"),
+            ("tsuuchi", "tuuti"),
+            ("fuairu", "huairu"),
+            ("konnichiha", "konitiha"),
+            ("jisho", "zisyo"),
+            ("jya", "ja"),
+            ("matcha", "mattya"),
+        ] {
+            assert_eq!(canonical_romaji(a), canonical_romaji(b), "{a} / {b}");
+        }
+    }
+
+    #[test]
+    fn canonical_romaji_is_idempotent() {
+        for s in ["shashin", "konnichiha", "matcha", "kohi-", "wo", "cocoa"] {
+            let once = canonical_romaji(s);
+            assert_eq!(canonical_romaji(&once), once);
+        }
+    }
+}
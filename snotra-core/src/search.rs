@@ -1,16 +1,32 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 
+use crate::arena::{CharSet, StrArena, StrId};
 use crate::history::HistoryStore;
 use crate::indexer::AppEntry;
-use crate::query::normalize_query;
+use crate::query::{fold_text, fold_text_with_spans, normalize_query};
+use crate::romaji::{canonical_romaji, romanize};
 use crate::ui_types::SearchResult;
 
 const GLOBAL_WEIGHT: i64 = 5;
 const QUERY_WEIGHT: i64 = 20;
 const FOLDER_EXPANSION_WEIGHT: i64 = 5;
+/// Multiplier of a scan path's `weight`.
+const SCAN_PATH_WEIGHT: i64 = 10;
+/// Matches on a shortcut's target file name rank below matches on its own name.
+const TARGET_MATCH_DIVISOR: i64 = 2;
+/// Base score of word-boundary matches: below any prefix match (10_000 - length),
+/// above substring (5_000 - position) and fuzzy matches.
+const WORD_BOUNDARY_SCORE: i64 = 7_000;
+/// Cost of each word of the name a word-boundary match leaves out.
+const SKIPPED_WORD_PENALTY: i64 = 10;
+/// Words beyond this are ignored by word-boundary matching.
+const MAX_BOUNDARY_WORDS: usize = 16;
+/// Matches on the romaji reading of a name rank just below the same match on the name.
+const ROMAJI_MATCH_PENALTY: i64 = 100;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchMode {
@@ -31,16 +47,82 @@ impl From<crate::config::SearchModeConfig> for SearchMode {
 
 pub struct SearchEngine {
     entries: Vec<AppEntry>,
-    lower_names: Vec<String>,
+    /// Lowercase match keys of every entry, parallel to `entries`.
+    keys: Vec<EntryKeys>,
+    /// Storage of all folded match keys.
+    arena: StrArena,
+    /// Keyword ids of all entries; `EntryKeys::keywords` indexes into this.
+    keyword_ids: Vec<StrId>,
     matcher: SkimMatcherV2,
 }
 
+/// Match keys of one entry, folded like queries (see `fold_text`).
+struct EntryKeys {
+    name: StrId,
+    /// Name split into words at spaces, punctuation and case changes (`power shell` for
+    /// `PowerShell`), for word-boundary matching.
+    name_words: StrId,
+    /// Romaji reading of the kana in the name (`memo帳` for `メモ帳`); empty without kana.
+    romaji: StrId,
+    /// Shortcut target file stem (e.g. `chrome`) or URL host (e.g. `github.com`);
+    /// empty when unknown.
+    target: StrId,
+    /// File name with extension, matched when the query contains a dot.
+    file_name: StrId,
+    /// Range of `keyword_ids` holding the entry's keywords; matched like targets.
+    keywords: (u32, u32),
+    /// Characters of all keys above, for prefiltering.
+    chars: CharSet,
+}
+
 impl SearchEngine {
     pub fn new(entries: Vec<AppEntry>) -> Self {
-        let lower_names = entries.iter().map(|e| e.name.to_lowercase()).collect();
+        let bytes = entries
+            .iter()
+            .map(|e| e.name.len() + file_name_of(&e.target_path).len())
+            .sum();
+        let mut arena = StrArena::with_capacity(entries.len() * 2, bytes);
+        let mut keyword_ids = Vec::new();
+        let keys = entries
+            .iter()
+            .map(|e| {
+                let mut chars = CharSet::default();
+                let mut intern = |s: &str| {
+                    let folded = fold_text(s);
+                    chars.extend(&folded);
+                    arena.intern(&folded)
+                };
+                let name = intern(&e.name);
+                let name_words = intern(&split_words(&e.name));
+                let romaji = intern(&romanize(&fold_text(&e.name)).unwrap_or_default());
+                let target = intern(
+                    e.shortcut
+                        .as_ref()
+                        .and_then(|s| s.target_stem())
+                        .or_else(|| e.url.as_ref().and_then(|u| u.host()))
+                        .unwrap_or_default(),
+                );
+                let file_name = intern(file_name_of(&e.target_path));
+                let start = keyword_ids.len() as u32;
+                keyword_ids.extend(e.keywords.iter().map(|k| intern(k)));
+                EntryKeys {
+                    name,
+                    name_words,
+                    romaji,
+                    target,
+                    file_name,
+                    keywords: (start, keyword_ids.len() as u32),
+                    chars,
+                }
+            })
+            .collect();
+        arena.finish();
+        keyword_ids.shrink_to_fit();
         Self {
             entries,
-            lower_names,
+            keys,
+            arena,
+            keyword_ids,
             matcher: SkimMatcherV2::default(),
         }
     }
@@ -58,20 +140,59 @@ impl SearchEngine {
         }
 
         let has_dot = norm_query.contains('.');
+        let query_chars = CharSet::of(&norm_query);
+        let romaji_query = canonical_romaji(&norm_query);
+        let romaji_chars = CharSet::of(&romaji_query);
+        let query_history = history.query_counts(&norm_query);
+        let score_key = |id: StrId| {
+            if id == StrArena::EMPTY {
+                None
+            } else {
+                match_score(mode, &self.matcher, self.arena.get(id), "", &norm_query)
+            }
+        };
+        let score_name = |keys: &EntryKeys| {
+            let words = self.arena.get(keys.name_words);
+            match_score(
+                mode,
+                &self.matcher,
+                self.arena.get(keys.name),
+                words,
+                &norm_query,
+            )
+        };
 
         let mut scored: Vec<(i64, u64, &AppEntry, &str)> = self
             .entries
             .iter()
-            .zip(self.lower_names.iter())
-            .filter_map(|(entry, lower_name)| {
-                let name_score =
-                    match_score_single_cached(mode, &self.matcher, lower_name, &norm_query);
+            .zip(self.keys.iter())
+            .filter(|(_, keys)| {
+                keys.chars.contains_all(query_chars)
+                    || (keys.romaji != StrArena::EMPTY && keys.chars.contains_all(romaji_chars))
+            })
+            .filter_map(|(entry, keys)| {
+                // エントリ名で一致しなければ名前のローマ字読み、リンク先の実行ファイル名 / URL のホスト名、キーワードで照合
+                let name_score = score_name(keys)
+                    .or_else(|| {
+                        if keys.romaji == StrArena::EMPTY {
+                            return None;
+                        }
+                        let romaji = self.arena.get(keys.romaji);
+                        match_score(mode, &self.matcher, romaji, "", &romaji_query)
+                            .map(|score| score - ROMAJI_MATCH_PENALTY)
+                    })
+                    .or_else(|| score_key(keys.target).map(|score| score / TARGET_MATCH_DIVISOR))
+                    .or_else(|| {
+                        let (start, end) = keys.keywords;
+                        self.keyword_ids[start as usize..end as usize]
+                            .iter()
+                            .filter_map(|&id| score_key(id))
+                            .max()
+                            .map(|score| score / TARGET_MATCH_DIVISOR)
+                    });
                 let score = if has_dot {
                     // ドットあり → entry.name とファイル名（拡張子込み）の両方で照合し、高い方を採用
-                    let fn_score = std::path::Path::new(&entry.target_path)
-                        .file_name()
-                        .and_then(|f| f.to_str())
-                        .and_then(|f| match_score_single(mode, &self.matcher, f, &norm_query));
+                    let fn_score = score_key(keys.file_name);
                     match (name_score, fn_score) {
                         (Some(a), Some(b)) => Some(a.max(b)),
                         (a, b) => a.or(b),
@@ -81,28 +202,46 @@ impl SearchEngine {
                     name_score
                 };
                 score.map(|base_score| {
-                    let global = history.global_count(&entry.target_path) as i64;
-                    let qcount = history.query_count(&norm_query, &entry.target_path) as i64;
+                    // 統合された重複エントリの履歴も合算する
+                    let global: i64 = history_paths(entry)
+                        .map(|p| history.global_count(p) as i64)
+                        .sum();
+                    let qcount: i64 = query_history.map_or(0, |counts| {
+                        history_paths(entry)
+                            .map(|p| counts.get(p).copied().unwrap_or(0) as i64)
+                            .sum()
+                    });
                     let folder_boost = if entry.is_folder {
                         history.folder_expansion_count(&entry.target_path) as i64
                             * FOLDER_EXPANSION_WEIGHT
                     } else {
                         0
                     };
-                    let combined =
-                        base_score + global * GLOBAL_WEIGHT + qcount * QUERY_WEIGHT + folder_boost;
-                    let last = history.last_launched(&entry.target_path).unwrap_or(0);
-                    (combined, last, entry, lower_name.as_str())
+                    let combined = base_score
+                        + global * GLOBAL_WEIGHT
+                        + qcount * QUERY_WEIGHT
+                        + folder_boost
+                        + entry.weight as i64 * SCAN_PATH_WEIGHT;
+                    let last = history_paths(entry)
+                        .filter_map(|p| history.last_launched(p))
+                        .max()
+                        .unwrap_or(0);
+                    (combined, last, entry, self.arena.get(keys.name))
                 })
             })
             .collect();
 
-        scored.sort_by(|a, b| {
+        let order = |a: &(i64, u64, &AppEntry, &str), b: &(i64, u64, &AppEntry, &str)| {
             b.0.cmp(&a.0)
                 .then_with(|| b.1.cmp(&a.1))
                 .then_with(|| a.3.cmp(b.3))
-        });
+        };
+        // Only the best `max_results` need sorting; broad queries match most of the catalog
+        if max_results > 0 && scored.len() > max_results {
+            scored.select_nth_unstable_by(max_results - 1, order);
+        }
         scored.truncate(max_results);
+        scored.sort_by(order);
 
         scored
             .into_iter()
@@ -111,6 +250,9 @@ impl SearchEngine {
                 path: entry.target_path.clone(),
                 is_folder: entry.is_folder,
                 is_error: false,
+                description: entry_description(entry),
+                kind: entry.kind,
+                highlights: highlight_indices(mode, &self.matcher, &entry.name, &norm_query),
             })
             .collect()
     }
@@ -119,20 +261,24 @@ impl SearchEngine {
         let path_to_entry: HashMap<&str, &AppEntry> = self
             .entries
             .iter()
-            .map(|e| (e.target_path.as_str(), e))
+            .flat_map(|e| history_paths(e).map(move |p| (p, e)))
             .collect();
 
+        let mut shown = HashSet::new();
         history
             .recent_launches()
             .into_iter()
             .take(max_results)
-            .filter_map(|path| {
-                path_to_entry.get(path).map(|entry| SearchResult {
-                    name: entry.name.clone(),
-                    path: entry.target_path.clone(),
-                    is_folder: entry.is_folder,
-                    is_error: false,
-                })
+            .filter_map(|path| path_to_entry.get(path))
+            .filter(|entry| shown.insert(entry.target_path.as_str()))
+            .map(|entry| SearchResult {
+                name: entry.name.clone(),
+                path: entry.target_path.clone(),
+                is_folder: entry.is_folder,
+                is_error: false,
+                description: entry_description(entry),
+                kind: entry.kind,
+                highlights: Vec::new(),
             })
             .collect()
     }
@@ -142,6 +288,306 @@ impl SearchEngine {
     }
 }
 
+/// Paths whose launch history belongs to the entry: its own and its collapsed duplicates'.
+fn history_paths(entry: &AppEntry) -> impl Iterator<Item = &str> {
+    std::iter::once(entry.target_path.as_str()).chain(entry.aliases.iter().map(String::as_str))
+}
+
+/// Secondary text for a result: the shortcut comment, else where the entry lives.
+/// Custom entries show their name, as their path is a command line.
+fn entry_description(entry: &AppEntry) -> Option<String> {
+    if entry.custom {
+        return Some(entry.name.clone());
+    }
+    entry
+        .shortcut
+        .as_ref()
+        .and_then(|s| s.description.clone())
+        .or_else(|| entry.context.clone())
+}
+
+/// Last path component, accepting both `\\` and `/` separators so Windows-style
+/// index paths behave the same on every host OS.
+fn file_name_of(path: &str) -> &str {
+    path.rsplit(['\\', '/']).next().unwrap_or(path)
+}
+
+/// Score of a normalized query against a key folded by `fold_text`. A query of several
+/// space-separated tokens matches when every token matches some word of the key, in any
+/// order; the token scores are summed. A single token is matched against the whole key.
+///
+/// `words` is the key split by `split_words` (empty to skip). In every mode, a query or
+/// token spelled by the starts of those words also matches, scored by `word_boundary_score`.
+pub(crate) fn match_score(
+    mode: SearchMode,
+    matcher: &SkimMatcherV2,
+    lower_key: &str,
+    words: &str,
+    query: &str,
+) -> Option<i64> {
+    if !query.contains(' ') {
+        return match_score_single_cached(mode, matcher, lower_key, query)
+            .max(word_boundary_score(words, query));
+    }
+    query
+        .split(' ')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            lower_key
+                .split_whitespace()
+                .filter_map(|word| match_score_single_cached(mode, matcher, word, token))
+                .max()
+                .max(word_boundary_score(words, token))
+        })
+        .sum()
+}
+
+/// Char indices of `name` matched by a normalized query, for highlighting. Follows
+/// `match_score` on the name: per token, the better of the mode's match and the
+/// word-boundary match. Empty when the name itself does not match (the entry was found by
+/// its target, keywords or romaji reading).
+pub(crate) fn highlight_indices(
+    mode: SearchMode,
+    matcher: &SkimMatcherV2,
+    name: &str,
+    query: &str,
+) -> Vec<usize> {
+    let (key, spans) = fold_text_with_spans(name);
+    let chars: Vec<char> = name.chars().collect();
+    let words: Vec<(Vec<char>, Vec<Range<usize>>)> = word_spans(&chars)
+        .into_iter()
+        .map(|word| {
+            let text: String = chars[word.clone()].iter().collect();
+            let (folded, spans) = fold_text_with_spans(&text);
+            let spans = spans
+                .into_iter()
+                .map(|s| s.start + word.start..s.end + word.start)
+                .collect();
+            (folded.chars().collect(), spans)
+        })
+        .filter(|(folded, _): &(Vec<char>, _)| !folded.is_empty())
+        .take(MAX_BOUNDARY_WORDS)
+        .collect();
+
+    let multi_token = query.contains(' ');
+    let mut indices = Vec::new();
+    for token in query.split(' ').filter(|token| !token.is_empty()) {
+        // Folded-key char positions of the mode's match.
+        let direct = if multi_token {
+            key_words(&key)
+                .filter_map(|(start, word)| {
+                    mode_indices(mode, matcher, word, token)
+                        .map(|(score, hits)| (score, hits.into_iter().map(|i| i + start).collect()))
+                })
+                .max_by_key(|(score, _): &(i64, Vec<usize>)| *score)
+        } else {
+            mode_indices(mode, matcher, &key, token)
+        };
+        let direct = direct.map(|(score, hits)| {
+            let hits: Vec<usize> = hits.into_iter().flat_map(|i| spans[i].clone()).collect();
+            (score, hits)
+        });
+        let boundary = boundary_indices(&words, token);
+        let best = match (direct, boundary) {
+            (Some(a), Some(b)) => Some(if b.0 > a.0 { b } else { a }),
+            (a, b) => a.or(b),
+        };
+        let Some((_, hits)) = best else {
+            return Vec::new();
+        };
+        indices.extend(hits);
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+/// Whitespace-separated words of a folded key with their starting char index.
+fn key_words(key: &str) -> impl Iterator<Item = (usize, &str)> {
+    key.split_whitespace().map(move |word| {
+        let byte = word.as_ptr() as usize - key.as_ptr() as usize;
+        (key[..byte].chars().count(), word)
+    })
+}
+
+/// `match_score_single_cached` with the matched char indices of `key`.
+fn mode_indices(
+    mode: SearchMode,
+    matcher: &SkimMatcherV2,
+    key: &str,
+    query: &str,
+) -> Option<(i64, Vec<usize>)> {
+    let score = match_score_single_cached(mode, matcher, key, query)?;
+    let indices = match mode {
+        SearchMode::Prefix => (0..query.chars().count()).collect(),
+        SearchMode::Substring => {
+            let start = key[..key.find(query)?].chars().count();
+            (start..start + query.chars().count()).collect()
+        }
+        SearchMode::Fuzzy => matcher.fuzzy_indices(key, query)?.1,
+    };
+    Some((score, indices))
+}
+
+/// `word_boundary_score` with the matched char indices of the name the words came from.
+fn boundary_indices(
+    words: &[(Vec<char>, Vec<Range<usize>>)],
+    query: &str,
+) -> Option<(i64, Vec<usize>)> {
+    let folded: Vec<Vec<char>> = words.iter().map(|(w, _)| w.clone()).collect();
+    let query: Vec<char> = query.chars().collect();
+    let mut matcher = BoundaryMatcher::new(&folded, &query);
+    let (skipped, first_taken) = matcher.best()?;
+    let indices = matcher
+        .chunks(first_taken)
+        .into_iter()
+        .flat_map(|(word, taken)| words[word].1[..taken].iter().cloned().flatten())
+        .collect();
+    Some((boundary_score(&folded, skipped), indices))
+}
+
+/// Words of `name`, folded and separated by single spaces. Words break at whitespace and
+/// punctuation, between a lowercase letter and an uppercase one (`PowerShell`), before the
+/// last capital of an uppercase run followed by lowercase (`HTMLEditor`), and between
+/// letters and digits (`Win7`).
+pub(crate) fn split_words(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let words: Vec<String> = word_spans(&chars)
+        .into_iter()
+        .map(|span| chars[span].iter().collect())
+        .collect();
+    fold_text(&words.join(" "))
+}
+
+/// Char ranges of the words of `chars`, as described at `split_words`.
+fn word_spans(chars: &[char]) -> Vec<Range<usize>> {
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            continue;
+        }
+        let continues = i.checked_sub(1).map(|p| chars[p]).is_some_and(|prev| {
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            let boundary = (prev.is_lowercase() && c.is_uppercase())
+                || (prev.is_uppercase() && c.is_uppercase() && next_lower)
+                || (prev.is_ascii_digit() != c.is_ascii_digit());
+            prev.is_alphanumeric() && !boundary
+        });
+        match spans.last_mut() {
+            Some(span) if continues => span.end = i + 1,
+            _ => spans.push(i..i + 1),
+        }
+    }
+    spans
+}
+
+/// Score for a query spelled by the starts of consecutive words, like `vsc` for
+/// `visual studio code` or `pshell` for `power shell`. Each used word contributes a
+/// non-empty prefix; words between them may be left out, at `SKIPPED_WORD_PENALTY` each.
+/// The match starts at the first word and spans at least two, so a plain prefix is left to
+/// the mode.
+fn word_boundary_score(words: &str, query: &str) -> Option<i64> {
+    if words.is_empty() || query.is_empty() {
+        return None;
+    }
+    let words: Vec<Vec<char>> = words
+        .split(' ')
+        .take(MAX_BOUNDARY_WORDS)
+        .map(|w| w.chars().collect())
+        .collect();
+    if words.len() < 2 {
+        return None;
+    }
+    let query: Vec<char> = query.chars().collect();
+    let (skipped, _) = BoundaryMatcher::new(&words, &query).best()?;
+    Some(boundary_score(&words, skipped))
+}
+
+fn boundary_score(words: &[Vec<char>], skipped: usize) -> i64 {
+    let length: usize = words.iter().map(|w| w.len()).sum();
+    WORD_BOUNDARY_SCORE - skipped as i64 * SKIPPED_WORD_PENALTY - length as i64
+}
+
+/// Memoized search for the fewest words left out when spelling a query by word prefixes.
+struct BoundaryMatcher<'a> {
+    words: &'a [Vec<char>],
+    query: &'a [char],
+    /// `(word, query position)` -> fewest skipped words; `None` until computed.
+    memo: Vec<Option<Option<usize>>>,
+}
+
+impl<'a> BoundaryMatcher<'a> {
+    fn new(words: &'a [Vec<char>], query: &'a [char]) -> Self {
+        Self {
+            words,
+            query,
+            memo: vec![None; (words.len() + 1) * (query.len() + 1)],
+        }
+    }
+
+    /// Fewest skipped words of a match starting at the first word, with how many query
+    /// chars that word spells.
+    fn best(&mut self) -> Option<(usize, usize)> {
+        if self.words.len() < 2 || self.query.is_empty() {
+            return None;
+        }
+        let first = common_prefix_len(&self.words[0], self.query);
+        (1..=first.min(self.query.len() - 1))
+            .filter_map(|taken| self.skipped(1, taken).map(|s| (s, taken)))
+            .min()
+    }
+
+    /// `(word, chars taken)` of each word used by the best match, which spells its first
+    /// `first_taken` chars with the first word.
+    fn chunks(&mut self, first_taken: usize) -> Vec<(usize, usize)> {
+        let mut chunks = vec![(0, first_taken)];
+        let (mut word, mut pos) = (1, first_taken);
+        while pos < self.query.len() && word < self.words.len() {
+            let Some(target) = self.skipped(word, pos) else {
+                break;
+            };
+            let common = common_prefix_len(&self.words[word], &self.query[pos..]);
+            let taken = (1..=common).find(|&n| self.skipped(word + 1, pos + n) == Some(target));
+            if let Some(n) = taken {
+                chunks.push((word, n));
+                pos += n;
+            }
+            word += 1;
+        }
+        chunks
+    }
+
+    /// Fewest words skipped to spell `query[pos..]` from `words[word..]`. Words after the
+    /// last one used are not counted.
+    fn skipped(&mut self, word: usize, pos: usize) -> Option<usize> {
+        if pos == self.query.len() {
+            return Some(0);
+        }
+        if word == self.words.len() {
+            return None;
+        }
+        let slot = word * (self.query.len() + 1) + pos;
+        if let Some(known) = self.memo[slot] {
+            return known;
+        }
+        let common = common_prefix_len(&self.words[word], &self.query[pos..]);
+        let taken = (1..=common)
+            .filter_map(|n| self.skipped(word + 1, pos + n))
+            .min();
+        let left_out = self.skipped(word + 1, pos).map(|s| s + 1);
+        let best = match (taken, left_out) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.memo[slot] = Some(best);
+        best
+    }
+}
+
+fn common_prefix_len(a: &[char], b: &[char]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
 /// Score using a pre-computed lowercase name (avoids repeated allocation).
 fn match_score_single_cached(
     mode: SearchMode,
@@ -162,22 +608,12 @@ fn match_score_single_cached(
     }
 }
 
-/// Score with on-the-fly lowercase (for file names from target_path).
-fn match_score_single(
-    mode: SearchMode,
-    matcher: &SkimMatcherV2,
-    name: &str,
-    query: &str,
-) -> Option<i64> {
-    let lname = name.to_lowercase();
-    match_score_single_cached(mode, matcher, &lname, query)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::history::HistoryStore;
-    use crate::indexer::AppEntry;
+    use crate::indexer::{AppEntry, EntryKind};
+    use crate::shortcut::{ShortcutInfo, UrlShortcut};
 
     fn make_entries(names: &[&str]) -> Vec<AppEntry> {
         names
@@ -186,6 +622,7 @@ mod tests {
                 name: n.to_string(),
                 target_path: format!("C:\\fake\\{}.lnk", n),
                 is_folder: false,
+                ..Default::default()
             })
             .collect()
     }
@@ -234,6 +671,90 @@ mod tests {
         assert!(!results[0].is_folder);
     }
 
+    fn shortcut_entry(name: &str, target: &str, description: Option<&str>) -> AppEntry {
+        AppEntry {
+            name: name.to_string(),
+            target_path: format!("C:\\fake\\{}.lnk", name),
+            is_folder: false,
+            kind: EntryKind::AppShortcut,
+            shortcut: Some(ShortcutInfo {
+                target: Some(target.to_string()),
+                description: description.map(str::to_string),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn search_matches_shortcut_target_name() {
+        let entries = vec![
+            shortcut_entry(
+                "Google Chrome",
+                "C:\\Program Files\\Google\\Chrome\\Application\\chrome.exe",
+                None,
+            ),
+            shortcut_entry("電卓", "C:\\Windows\\System32\\calc.exe", None),
+        ];
+        let engine = SearchEngine::new(entries);
+
+        let results = engine.search("calc", 8, &empty_history(), SearchMode::Prefix);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "電卓");
+
+        let results = engine.search("chrome", 8, &empty_history(), SearchMode::Prefix);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Google Chrome");
+    }
+
+    #[test]
+    fn search_matches_url_title_and_host() {
+        let entries = vec![AppEntry {
+            name: "社内Wiki".to_string(),
+            target_path: "C:\\fake\\社内Wiki.url".to_string(),
+            kind: EntryKind::Url,
+            url: Some(UrlShortcut {
+                url: "https://www.wiki.example.com/start".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }];
+        let engine = SearchEngine::new(entries);
+
+        let by_title = engine.search("社内", 8, &empty_history(), SearchMode::Prefix);
+        assert_eq!(by_title.len(), 1);
+        let by_host = engine.search("wiki.ex", 8, &empty_history(), SearchMode::Prefix);
+        assert_eq!(by_host.len(), 1);
+        assert_eq!(by_host[0].path, "C:\\fake\\社内Wiki.url");
+        assert_eq!(by_host[0].kind, EntryKind::Url);
+    }
+
+    #[test]
+    fn search_prefers_name_match_over_target_match() {
+        let entries = vec![
+            shortcut_entry("Code Helper", "C:\\tools\\code.exe", None),
+            shortcut_entry("Visual Studio Code", "C:\\vscode\\code.exe", None),
+        ];
+        let engine = SearchEngine::new(entries);
+        let results = engine.search("code", 8, &empty_history(), SearchMode::Prefix);
+        assert_eq!(results[0].name, "Code Helper");
+    }
+
+    #[test]
+    fn search_result_carries_shortcut_description() {
+        let engine = SearchEngine::new(vec![shortcut_entry(
+            "Editor",
+            "C:\\editor.exe",
+            Some("テキストを編集します"),
+        )]);
+        let results = engine.search("edit", 8, &empty_history(), SearchMode::Prefix);
+        assert_eq!(
+            results[0].description.as_deref(),
+            Some("テキストを編集します")
+        );
+        assert_eq!(results[0].kind, EntryKind::AppShortcut);
+    }
+
     #[test]
     fn search_prefix_mode_matches_only_prefix() {
         let entries = make_entries(&["Notepad", "Pad Tool"]);
@@ -251,6 +772,170 @@ mod tests {
         assert_eq!(results.len(), 1);
     }
 
+    #[test]
+    fn search_matches_tokens_in_any_order_in_every_mode() {
+        let entries = make_entries(&["Visual Studio Code", "Visual Studio Installer", "Code Helper"]);
+        let engine = SearchEngine::new(entries);
+        for mode in [SearchMode::Prefix, SearchMode::Substring, SearchMode::Fuzzy] {
+            let results = engine.search("code studio", 8, &empty_history(), mode);
+            let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+            assert_eq!(names, vec!["Visual Studio Code"], "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn every_token_must_match_a_word() {
+        let entries = make_entries(&["Visual Studio Code", "Notepad"]);
+        let engine = SearchEngine::new(entries);
+        assert!(engine.search("code pad", 8, &empty_history(), SearchMode::Substring).is_empty());
+        // Prefix mode matches tokens against word starts only.
+        assert!(engine.search("ode vis", 8, &empty_history(), SearchMode::Prefix).is_empty());
+        assert_eq!(engine.search("ode vis", 8, &empty_history(), SearchMode::Substring).len(), 1);
+    }
+
+    #[test]
+    fn fuzzy_tokens_match_within_single_words() {
+        let entries = make_entries(&["Visual Studio Code", "Studio One"]);
+        let engine = SearchEngine::new(entries);
+        let results = engine.search("stdo cde", 8, &empty_history(), SearchMode::Fuzzy);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Visual Studio Code");
+        let reversed = engine.search("cde stdo", 8, &empty_history(), SearchMode::Fuzzy);
+        assert_eq!(reversed, results);
+    }
+
+    #[test]
+    fn splits_words_at_case_changes_punctuation_and_digits() {
+        assert_eq!(split_words("Visual Studio Code"), "visual studio code");
+        assert_eq!(split_words("PowerShell"), "power shell");
+        assert_eq!(split_words("HTMLEditor"), "html editor");
+        assert_eq!(split_words("7-Zip File Manager"), "7 zip file manager");
+        assert_eq!(split_words("Win7  (x64)"), "win 7 x 64");
+        assert_eq!(split_words("メモ帳"), "めも帳");
+        assert_eq!(split_words("ＰｏｗｅｒＳｈｅｌｌ"), "power shell");
+    }
+
+    #[test]
+    fn word_boundary_scores_sit_between_prefix_and_fuzzy() {
+        let vsc = word_boundary_score("visual studio code", "vsc").expect("initials");
+        assert!(vsc < 10_000 - "visual studio code".len() as i64);
+        assert!(vsc > 5_000);
+        assert!(word_boundary_score("power shell", "pshell").is_some());
+        assert!(word_boundary_score("power shell", "powsh").is_some());
+        // Skipping a word costs a little.
+        let skipped = word_boundary_score("visual studio code", "vc").expect("skip");
+        assert!(skipped < word_boundary_score("visual studio code", "vs").unwrap());
+        // A plain prefix of the first word, or a match not starting there, is not this tier.
+        assert_eq!(word_boundary_score("power shell", "pow"), None);
+        assert_eq!(word_boundary_score("visual studio code", "sc"), None);
+        assert_eq!(word_boundary_score("notepad", "np"), None);
+    }
+
+    #[test]
+    fn acronyms_match_in_every_mode_and_rank_above_scattered_matches() {
+        let entries = make_entries(&[
+            "Visual Studio Code",
+            "Vivaldi Shortcuts",
+            "PowerShell",
+            "Photoshop",
+        ]);
+        let engine = SearchEngine::new(entries);
+        for mode in [SearchMode::Prefix, SearchMode::Substring, SearchMode::Fuzzy] {
+            let results = engine.search("vsc", 8, &empty_history(), mode);
+            assert_eq!(results[0].name, "Visual Studio Code", "{:?}", mode);
+            let results = engine.search("ps", 8, &empty_history(), mode);
+            assert_eq!(results[0].name, "PowerShell", "{:?}", mode);
+        }
+        // Prefix matches still come first.
+        let engine = SearchEngine::new(make_entries(&["PowerShell", "PSPad"]));
+        let results = engine.search("ps", 8, &empty_history(), SearchMode::Prefix);
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["PSPad", "PowerShell"]);
+    }
+
+    #[test]
+    fn acronym_can_be_one_of_several_tokens() {
+        let engine = SearchEngine::new(make_entries(&[
+            "Visual Studio Code",
+            "Visual Studio Installer",
+        ]));
+        let results = engine.search("code vs", 8, &empty_history(), SearchMode::Prefix);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Visual Studio Code");
+    }
+
+    #[test]
+    fn search_folds_width_kana_and_diacritics() {
+        let engine = SearchEngine::new(make_entries(&["メモ帳", "Firefox", "Café Manager"]));
+        for query in ["ﾒﾓ", "めも", "メモ"] {
+            let results = engine.search(query, 8, &empty_history(), SearchMode::Prefix);
+            assert_eq!(results.len(), 1, "{query}");
+            assert_eq!(results[0].name, "メモ帳");
+        }
+        let results = engine.search("ｆｉｒｅ", 8, &empty_history(), SearchMode::Prefix);
+        assert_eq!(results[0].name, "Firefox");
+        let results = engine.search("cafe", 8, &empty_history(), SearchMode::Prefix);
+        assert_eq!(results[0].name, "Café Manager");
+    }
+
+    #[test]
+    fn romaji_query_matches_kana_names() {
+        let engine = SearchEngine::new(make_entries(&["メモ帳", "画像フォルダ", "しゃしん"]));
+        let results = engine.search("memo", 8, &empty_history(), SearchMode::Prefix);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "メモ帳");
+        let results = engine.search("foruda", 8, &empty_history(), SearchMode::Substring);
+        assert_eq!(results[0].name, "画像フォルダ");
+        // Hepburn and IME spellings both match.
+        for query in ["shashin", "syasin"] {
+            let results = engine.search(query, 8, &empty_history(), SearchMode::Prefix);
+            assert_eq!(results.len(), 1, "{query}");
+            assert_eq!(results[0].name, "しゃしん");
+        }
+    }
+
+    #[test]
+    fn romaji_matches_rank_below_direct_matches() {
+        let engine = SearchEngine::new(make_entries(&["メモ帳", "Memo"]));
+        let results = engine.search("memo", 8, &empty_history(), SearchMode::Prefix);
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["Memo", "メモ帳"]);
+    }
+
+    #[test]
+    fn results_carry_highlights_for_each_mode() {
+        let engine = SearchEngine::new(make_entries(&["Visual Studio Code"]));
+        let highlights = |query: &str, mode| {
+            engine.search(query, 8, &empty_history(), mode)[0].highlights.clone()
+        };
+        assert_eq!(highlights("visual", SearchMode::Prefix), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(highlights("stud", SearchMode::Substring), vec![7, 8, 9, 10]);
+        assert_eq!(highlights("vsd", SearchMode::Fuzzy).len(), 3);
+        // Word-boundary matches highlight the word starts.
+        assert_eq!(highlights("vsc", SearchMode::Prefix), vec![0, 7, 14]);
+        assert_eq!(highlights("code vis", SearchMode::Prefix), vec![0, 1, 2, 14, 15, 16, 17]);
+    }
+
+    #[test]
+    fn highlights_map_to_original_chars() {
+        let matcher = SkimMatcherV2::default();
+        // "ｶﾞｲﾄﾞ" folds to three chars; each covers its voiced mark.
+        assert_eq!(
+            highlight_indices(SearchMode::Prefix, &matcher, "ｶﾞｲﾄﾞ", &normalize_query("ガイ")),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            highlight_indices(SearchMode::Substring, &matcher, "Mon Café", "cafe"),
+            vec![4, 5, 6, 7]
+        );
+        assert_eq!(
+            highlight_indices(SearchMode::Prefix, &matcher, "PowerShell", "pshell"),
+            vec![0, 5, 6, 7, 8, 9]
+        );
+        // Matches found only through other keys have nothing to highlight.
+        assert!(highlight_indices(SearchMode::Prefix, &matcher, "メモ帳", "memo").is_empty());
+    }
+
     #[test]
     fn search_with_extension_matches_stem_entry() {
         // "SSP.exe" と入力して、name="SSP", target_path="C:\\fake\\SSP.exe" にマッチする
@@ -258,6 +943,7 @@ mod tests {
             name: "SSP".to_string(),
             target_path: "C:\\fake\\SSP.exe".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("SSP.exe", 8, &empty_history(), SearchMode::Prefix);
@@ -271,6 +957,7 @@ mod tests {
             name: "SSP".to_string(),
             target_path: "C:\\fake\\SSP.exe".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("ssp.exe", 8, &empty_history(), SearchMode::Substring);
@@ -283,6 +970,7 @@ mod tests {
             name: "SSP".to_string(),
             target_path: "C:\\fake\\SSP.exe".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("ssp.exe", 8, &empty_history(), SearchMode::Fuzzy);
@@ -306,11 +994,13 @@ mod tests {
                 name: "SSP".to_string(),
                 target_path: "C:\\fake\\SSP.exe".to_string(),
                 is_folder: false,
+                ..Default::default()
             },
             AppEntry {
                 name: "FileZilla".to_string(),
                 target_path: "C:\\fake\\FileZilla.exe".to_string(),
                 is_folder: false,
+                ..Default::default()
             },
         ];
         let engine = SearchEngine::new(entries);
@@ -326,6 +1016,7 @@ mod tests {
             name: "SSP".to_string(),
             target_path: "C:\\fake\\SSP.lnk".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("ssp.exe", 8, &empty_history(), SearchMode::Prefix);
@@ -339,6 +1030,7 @@ mod tests {
             name: "SSP".to_string(),
             target_path: "C:\\fake\\SSP.exe".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("SSP.", 8, &empty_history(), SearchMode::Fuzzy);
@@ -353,6 +1045,7 @@ mod tests {
             name: "SSP".to_string(),
             target_path: "C:\\fake\\SSP.exe".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("SSP.e", 8, &empty_history(), SearchMode::Fuzzy);
@@ -367,6 +1060,7 @@ mod tests {
             name: "SSP".to_string(),
             target_path: "C:\\fake\\SSP.exe".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("SSP.ex", 8, &empty_history(), SearchMode::Fuzzy);
@@ -381,6 +1075,7 @@ mod tests {
             name: "Dr.Web".to_string(),
             target_path: "C:\\fake\\drweb32w.exe".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("Dr.Web", 8, &empty_history(), SearchMode::Fuzzy);
@@ -395,6 +1090,7 @@ mod tests {
             name: "Dr.Web".to_string(),
             target_path: "C:\\fake\\drweb32w.exe".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("dr.w", 8, &empty_history(), SearchMode::Fuzzy);
@@ -409,6 +1105,7 @@ mod tests {
             name: "hoge".to_string(),
             target_path: "C:\\fake\\hoge.exe.bak".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("hoge.exe", 8, &empty_history(), SearchMode::Fuzzy);
@@ -423,6 +1120,7 @@ mod tests {
             name: "hoge".to_string(),
             target_path: "C:\\fake\\hoge.exe.bak".to_string(),
             is_folder: false,
+            ..Default::default()
         }];
         let engine = SearchEngine::new(entries);
         let results = engine.search("hoge.exe.bak", 8, &empty_history(), SearchMode::Fuzzy);
@@ -437,4 +1135,107 @@ mod tests {
         let results = engine.recent_history(&empty_history(), 8);
         assert!(results.is_empty());
     }
+
+    #[test]
+    fn history_of_collapsed_duplicates_counts_for_kept_entry() {
+        let entries = vec![
+            AppEntry {
+                name: "Editor".to_string(),
+                target_path: "C:\\Menu\\Editor.lnk".to_string(),
+                aliases: vec!["C:\\Desktop\\Editor.lnk".to_string()],
+                ..Default::default()
+            },
+            AppEntry {
+                name: "Edge".to_string(),
+                target_path: "C:\\Menu\\Edge.lnk".to_string(),
+                ..Default::default()
+            },
+        ];
+        let engine = SearchEngine::new(entries);
+        let mut history = empty_history();
+        history.record_launch("C:\\Desktop\\Editor.lnk", "ed");
+        history.record_launch("C:\\Menu\\Edge.lnk", "ed");
+        history.record_launch("C:\\Menu\\Editor.lnk", "ed");
+
+        let results = engine.search("ed", 8, &history, SearchMode::Prefix);
+        assert_eq!(results[0].name, "Editor");
+
+        let recent = engine.recent_history(&history, 8);
+        let names: Vec<&str> = recent.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names.iter().filter(|n| **n == "Editor").count(), 1);
+        assert!(names.contains(&"Edge"));
+    }
+
+    fn weighted_entry(name: &str, dir: &str, weight: i32) -> AppEntry {
+        AppEntry {
+            name: name.to_string(),
+            target_path: format!("C:\\{}\\{}.lnk", dir, name),
+            weight,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn scan_path_weight_reorders_equal_matches() {
+        let entries = vec![
+            weighted_entry("Report", "Docs", 0),
+            weighted_entry("Report", "Menu", 2),
+            weighted_entry("Report", "Archive", -1),
+        ];
+        let engine = SearchEngine::new(entries);
+        let results = engine.search("report", 8, &empty_history(), SearchMode::Prefix);
+        let paths: Vec<&str> = results.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "C:\\Menu\\Report.lnk",
+                "C:\\Docs\\Report.lnk",
+                "C:\\Archive\\Report.lnk"
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_path_weight_adds_to_history_score() {
+        let entries = vec![
+            weighted_entry("Notes", "Docs", 0),
+            weighted_entry("Notepad", "Menu", 1),
+        ];
+        let engine = SearchEngine::new(entries);
+        let mut history = empty_history();
+        // Three launches outweigh one weight point (worth two launches).
+        for _ in 0..3 {
+            history.record_launch("C:\\Docs\\Notes.lnk", "other");
+        }
+        let results = engine.search("note", 8, &history, SearchMode::Substring);
+        assert_eq!(results[0].name, "Notes");
+
+        history = empty_history();
+        history.record_launch("C:\\Docs\\Notes.lnk", "other");
+        let results = engine.search("note", 8, &history, SearchMode::Substring);
+        assert_eq!(results[0].name, "Notepad");
+    }
+
+    #[test]
+    fn custom_entries_match_by_keyword_below_name() {
+        let entries = vec![
+            AppEntry {
+                name: "Terminal".to_string(),
+                target_path: "wt.exe -p PowerShell".to_string(),
+                keywords: vec!["PowerShell".to_string()],
+                custom: true,
+                ..Default::default()
+            },
+            AppEntry {
+                name: "PowerToys".to_string(),
+                target_path: "C:\\Menu\\PowerToys.lnk".to_string(),
+                ..Default::default()
+            },
+        ];
+        let engine = SearchEngine::new(entries);
+        let results = engine.search("power", 8, &empty_history(), SearchMode::Prefix);
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["PowerToys", "Terminal"]);
+        assert_eq!(results[1].description.as_deref(), Some("Terminal"));
+    }
 }
@@ -0,0 +1,542 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// MS-SHLLINK: Shell Link (.LNK) Binary File Format
+const HEADER_SIZE: u32 = 0x4C;
+const LINK_CLSID: [u8; 16] = [
+    0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x0000_0001;
+const HAS_LINK_INFO: u32 = 0x0000_0002;
+const HAS_NAME: u32 = 0x0000_0004;
+const HAS_RELATIVE_PATH: u32 = 0x0000_0008;
+const HAS_WORKING_DIR: u32 = 0x0000_0010;
+const HAS_ARGUMENTS: u32 = 0x0000_0020;
+const HAS_ICON_LOCATION: u32 = 0x0000_0040;
+const IS_UNICODE: u32 = 0x0000_0080;
+
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x1;
+const COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX: u32 = 0x2;
+
+const ENVIRONMENT_VARIABLE_BLOCK: u32 = 0xA000_0001;
+const ICON_ENVIRONMENT_BLOCK: u32 = 0xA000_0007;
+const ENVIRONMENT_BLOCK_SIZE: usize = 0x314;
+
+/// Largest shortcut file we are willing to read; real ones are a few KB.
+const MAX_SHORTCUT_SIZE: u64 = 1024 * 1024;
+
+/// Information stored in a `.lnk` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShortcutInfo {
+    /// Absolute target path. May contain environment variables (`%windir%\...`).
+    pub target: Option<String>,
+    pub arguments: Option<String>,
+    pub working_dir: Option<String>,
+    /// Comment shown as the shortcut's tooltip. Resource references (`@dll,-id`) are dropped.
+    pub description: Option<String>,
+    pub icon_location: Option<String>,
+    pub icon_index: i32,
+}
+
+impl ShortcutInfo {
+    /// File name of the target without extension, e.g. `chrome` for `...\chrome.exe`.
+    pub fn target_stem(&self) -> Option<&str> {
+        let target = self.target.as_deref()?;
+        let name = target.rsplit(['\\', '/']).next().unwrap_or(target);
+        let stem = match name.rfind('.') {
+            Some(idx) if idx > 0 => &name[..idx],
+            _ => name,
+        };
+        (!stem.is_empty()).then_some(stem)
+    }
+}
+
+/// Contents of an Internet shortcut (`.url`) or any other entry that opens a URL.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UrlShortcut {
+    pub url: String,
+    pub icon_file: Option<String>,
+    pub icon_index: i32,
+}
+
+impl UrlShortcut {
+    /// Host name without a leading `www.`, e.g. `github.com` for `https://www.github.com/foo`.
+    pub fn host(&self) -> Option<&str> {
+        let (_, rest) = self.url.split_once("://")?;
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+        let host_port = authority.rsplit('@').next().unwrap_or(authority);
+        let host = match host_port.rfind(':') {
+            Some(idx) if !host_port.ends_with(']') => &host_port[..idx],
+            _ => host_port,
+        };
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        (!host.is_empty()).then_some(host)
+    }
+}
+
+/// Read an Internet shortcut (`.url`) file.
+pub fn read_url_shortcut(path: &Path) -> Option<UrlShortcut> {
+    let meta = std::fs::metadata(path).ok()?;
+    if meta.len() > MAX_SHORTCUT_SIZE {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    parse_url_shortcut(&decode_text(&bytes))
+}
+
+/// Parse the `[InternetShortcut]` section of a `.url` file. Keys are case-insensitive.
+pub fn parse_url_shortcut(text: &str) -> Option<UrlShortcut> {
+    let mut in_section = false;
+    let mut shortcut = UrlShortcut::default();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('[') && line.ends_with(']') {
+            in_section = line[1..line.len() - 1]
+                .trim()
+                .eq_ignore_ascii_case("InternetShortcut");
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "url" => shortcut.url = value.to_string(),
+            "iconfile" if !value.is_empty() => shortcut.icon_file = Some(value.to_string()),
+            "iconindex" => shortcut.icon_index = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    (!shortcut.url.is_empty()).then_some(shortcut)
+}
+
+/// INI files may be UTF-8 (with or without BOM), UTF-16LE with BOM, or the ANSI code page.
+fn decode_text(bytes: &[u8]) -> String {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8_lossy(rest).into_owned();
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return decode_utf16(rest);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => decode_ansi(bytes),
+    }
+}
+
+/// Read and parse a shortcut file. A relative-only target is resolved against the file's folder.
+pub fn read_shortcut(path: &Path) -> Option<ShortcutInfo> {
+    let meta = std::fs::metadata(path).ok()?;
+    if meta.len() > MAX_SHORTCUT_SIZE {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    let parsed = parse(&bytes)?;
+    let relative_path = parsed.relative_path.clone();
+    let mut info = parsed.into_info();
+    if info.target.is_none()
+        && let Some(relative) = relative_path
+        && let Some(dir) = path.parent()
+    {
+        info.target = Some(resolve_relative(dir, &relative).to_string_lossy().to_string());
+    }
+    Some(info)
+}
+
+/// Join a relative link path onto `dir`, dropping `.` and resolving `..` components.
+/// `..` never climbs above the root of `dir`.
+fn resolve_relative(dir: &Path, relative: &str) -> PathBuf {
+    let mut target = dir.to_path_buf();
+    for part in relative.split(['\\', '/']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                target.pop();
+            }
+            _ => target.push(part),
+        }
+    }
+    target
+}
+
+/// Parse the contents of a `.lnk` file. Returns None when the header is not a shell link.
+pub fn parse_shortcut(bytes: &[u8]) -> Option<ShortcutInfo> {
+    parse(bytes).map(ParsedLink::into_info)
+}
+
+#[derive(Default)]
+struct ParsedLink {
+    icon_index: i32,
+    link_info_target: Option<String>,
+    name: Option<String>,
+    relative_path: Option<String>,
+    working_dir: Option<String>,
+    arguments: Option<String>,
+    icon_location: Option<String>,
+    env_target: Option<String>,
+    env_icon: Option<String>,
+}
+
+impl ParsedLink {
+    fn into_info(self) -> ShortcutInfo {
+        let target = self
+            .link_info_target
+            .or(self.env_target)
+            .filter(|t| !t.is_empty());
+        ShortcutInfo {
+            target,
+            arguments: self.arguments,
+            working_dir: self.working_dir,
+            description: self.name.filter(|d| !d.starts_with('@')),
+            icon_location: self.icon_location.or(self.env_icon),
+            icon_index: self.icon_index,
+        }
+    }
+}
+
+fn parse(bytes: &[u8]) -> Option<ParsedLink> {
+    if read_u32(bytes, 0)? != HEADER_SIZE || bytes.get(4..20)? != LINK_CLSID {
+        return None;
+    }
+    let flags = read_u32(bytes, 20)?;
+    let mut parsed = ParsedLink {
+        icon_index: read_u32(bytes, 56)? as i32,
+        ..Default::default()
+    };
+    let mut pos = HEADER_SIZE as usize;
+
+    if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+        pos += 2 + read_u16(bytes, pos)? as usize;
+    }
+
+    if flags & HAS_LINK_INFO != 0 {
+        let size = read_u32(bytes, pos)? as usize;
+        let link_info = bytes.get(pos..pos.checked_add(size)?)?;
+        parsed.link_info_target = parse_link_info(link_info);
+        pos += size;
+    }
+
+    let unicode = flags & IS_UNICODE != 0;
+    let string_fields = [
+        (HAS_NAME, &mut parsed.name),
+        (HAS_RELATIVE_PATH, &mut parsed.relative_path),
+        (HAS_WORKING_DIR, &mut parsed.working_dir),
+        (HAS_ARGUMENTS, &mut parsed.arguments),
+        (HAS_ICON_LOCATION, &mut parsed.icon_location),
+    ];
+    for (flag, field) in string_fields {
+        if flags & flag == 0 {
+            continue;
+        }
+        let (value, next) = read_string_data(bytes, pos, unicode)?;
+        *field = Some(value).filter(|v| !v.is_empty());
+        pos = next;
+    }
+
+    // ExtraData is optional; stop quietly at the first malformed block.
+    while let Some(size) = read_u32(bytes, pos).map(|s| s as usize) {
+        if size < 8 {
+            break;
+        }
+        let Some(block) = pos.checked_add(size).and_then(|end| bytes.get(pos..end)) else {
+            break;
+        };
+        match read_u32(block, 4) {
+            Some(ENVIRONMENT_VARIABLE_BLOCK) => parsed.env_target = parse_environment_block(block),
+            Some(ICON_ENVIRONMENT_BLOCK) => parsed.env_icon = parse_environment_block(block),
+            _ => {}
+        }
+        pos += size;
+    }
+
+    Some(parsed)
+}
+
+/// Target path from a LinkInfo structure (local volume or network share).
+fn parse_link_info(info: &[u8]) -> Option<String> {
+    let header_size = read_u32(info, 4)? as usize;
+    let flags = read_u32(info, 8)?;
+    let suffix_offset = read_u32(info, 24)? as usize;
+    let has_unicode = header_size >= 0x24;
+
+    let suffix = if has_unicode {
+        read_u32(info, 32).and_then(|off| read_utf16z(info, off as usize))
+    } else {
+        None
+    }
+    .or_else(|| read_ansiz(info, suffix_offset))
+    .unwrap_or_default();
+
+    let base = if flags & VOLUME_ID_AND_LOCAL_BASE_PATH != 0 {
+        let unicode_base = if has_unicode {
+            read_u32(info, 28).and_then(|off| read_utf16z(info, off as usize))
+        } else {
+            None
+        };
+        unicode_base.or_else(|| read_ansiz(info, read_u32(info, 16)? as usize))?
+    } else if flags & COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX != 0 {
+        let cnrl_offset = read_u32(info, 20)? as usize;
+        let cnrl = info.get(cnrl_offset..)?;
+        let net_name_offset = read_u32(cnrl, 8)? as usize;
+        let unicode_net_name = if net_name_offset > 0x14 {
+            read_u32(cnrl, 20).and_then(|off| read_utf16z(cnrl, off as usize))
+        } else {
+            None
+        };
+        let net_name = unicode_net_name.or_else(|| read_ansiz(cnrl, net_name_offset))?;
+        if suffix.is_empty() || net_name.ends_with('\\') {
+            net_name
+        } else {
+            format!("{}\\", net_name)
+        }
+    } else {
+        return None;
+    };
+
+    Some(base + &suffix)
+}
+
+/// EnvironmentVariableDataBlock / IconEnvironmentDataBlock: ANSI then Unicode path.
+fn parse_environment_block(block: &[u8]) -> Option<String> {
+    if block.len() < ENVIRONMENT_BLOCK_SIZE {
+        return None;
+    }
+    let unicode = block.get(268..ENVIRONMENT_BLOCK_SIZE)?;
+    read_utf16z(unicode, 0)
+        .filter(|s| !s.is_empty())
+        .or_else(|| read_ansiz(block.get(8..268)?, 0))
+        .filter(|s| !s.is_empty())
+}
+
+/// StringData: a character count followed by UTF-16 or ANSI characters (not terminated).
+fn read_string_data(bytes: &[u8], pos: usize, unicode: bool) -> Option<(String, usize)> {
+    let count = read_u16(bytes, pos)? as usize;
+    let start = pos + 2;
+    if unicode {
+        let end = start.checked_add(count * 2)?;
+        Some((decode_utf16(bytes.get(start..end)?), end))
+    } else {
+        let end = start.checked_add(count)?;
+        Some((decode_ansi(bytes.get(start..end)?), end))
+    }
+}
+
+fn read_utf16z(bytes: &[u8], offset: usize) -> Option<String> {
+    let data = bytes.get(offset..)?;
+    let len = data
+        .chunks_exact(2)
+        .position(|c| c == [0, 0])
+        .map(|units| units * 2)?;
+    Some(decode_utf16(&data[..len]))
+}
+
+fn read_ansiz(bytes: &[u8], offset: usize) -> Option<String> {
+    let data = bytes.get(offset..)?;
+    let len = data.iter().position(|&b| b == 0)?;
+    Some(decode_ansi(&data[..len]))
+}
+
+fn decode_utf16(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// ANSI strings are written in the system code page; Japanese Windows uses CP932 (Shift_JIS).
+fn decode_ansi(bytes: &[u8]) -> String {
+    if bytes.is_ascii() {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+    let (decoded, _, _) = encoding_rs::SHIFT_JIS.decode(bytes);
+    decoded.into_owned()
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let b = bytes.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let b = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_bytes!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/lnk/",
+                $name
+            ))
+        };
+    }
+
+    #[test]
+    fn parses_unicode_shortcut_with_all_strings() {
+        let info = parse_shortcut(fixture!("unicode_full.lnk")).expect("parse");
+        assert_eq!(
+            info.target.as_deref(),
+            Some("C:\\Program Files\\Editor\\editor.exe")
+        );
+        assert_eq!(info.description.as_deref(), Some("テキストエディター"));
+        assert_eq!(
+            info.working_dir.as_deref(),
+            Some("C:\\Users\\Public\\Documents")
+        );
+        assert_eq!(info.arguments.as_deref(), Some("--new-window \"a b.txt\""));
+        assert_eq!(
+            info.icon_location.as_deref(),
+            Some("C:\\Program Files\\Editor\\editor.exe")
+        );
+        assert_eq!(info.icon_index, 2);
+        assert_eq!(info.target_stem(), Some("editor"));
+    }
+
+    #[test]
+    fn prefers_unicode_link_info_paths() {
+        let info = parse_shortcut(fixture!("unicode_linkinfo.lnk")).expect("parse");
+        assert_eq!(info.target.as_deref(), Some("C:\\ツール\\アプリ.exe"));
+        assert_eq!(info.description, None);
+        assert_eq!(info.arguments, None);
+    }
+
+    #[test]
+    fn decodes_ansi_strings_as_cp932() {
+        let info = parse_shortcut(fixture!("ansi_cp932.lnk")).expect("parse");
+        assert_eq!(info.target.as_deref(), Some("C:\\ゲーム\\起動.exe"));
+        assert_eq!(info.description.as_deref(), Some("ゲームを起動"));
+        assert_eq!(info.arguments.as_deref(), Some("/fullscreen"));
+    }
+
+    #[test]
+    fn falls_back_to_environment_block_target() {
+        let info = parse_shortcut(fixture!("env_target.lnk")).expect("parse");
+        assert_eq!(
+            info.target.as_deref(),
+            Some("%windir%\\system32\\cmd.exe")
+        );
+        // Resource-string descriptions cannot be resolved without the DLL.
+        assert_eq!(info.description, None);
+        assert_eq!(
+            info.icon_location.as_deref(),
+            Some("%SystemRoot%\\system32\\imageres.dll")
+        );
+        assert_eq!(info.target_stem(), Some("cmd"));
+    }
+
+    #[test]
+    fn joins_network_share_and_suffix() {
+        let info = parse_shortcut(fixture!("network.lnk")).expect("parse");
+        assert_eq!(
+            info.target.as_deref(),
+            Some("\\\\server\\share\\tools\\report.exe")
+        );
+    }
+
+    #[test]
+    fn resolves_relative_only_target_against_shortcut_folder() {
+        let dir = std::env::temp_dir().join("snotra_lnk_test_relative");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let lnk = dir.join("tool.lnk");
+        std::fs::write(&lnk, fixture!("relative_only.lnk")).unwrap();
+
+        assert_eq!(parse_shortcut(fixture!("relative_only.lnk")).unwrap().target, None);
+        let info = read_shortcut(&lnk).expect("read");
+        assert_eq!(
+            info.target,
+            Some(dir.join("bin").join("tool.exe").to_string_lossy().to_string())
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn relative_target_components_are_normalized() {
+        let dir = Path::new("/apps/suite/bin");
+        assert_eq!(
+            resolve_relative(dir, "..\\tools\\.\\run.exe"),
+            Path::new("/apps/suite/tools/run.exe")
+        );
+        assert_eq!(resolve_relative(dir, "../../../../x.exe"), Path::new("/x.exe"));
+    }
+
+    #[test]
+    fn parses_internet_shortcut() {
+        let text = "[DEFAULT]\r\nBASEURL=https://ignored.example/\r\n\
+                    [InternetShortcut]\r\nURL=https://www.github.com:443/user/repo?tab=1\r\n\
+                    IconFile=C:\\icons\\gh.ico\r\nIconIndex=3\r\n";
+        let shortcut = parse_url_shortcut(text).expect("parse");
+        assert_eq!(shortcut.url, "https://www.github.com:443/user/repo?tab=1");
+        assert_eq!(shortcut.icon_file.as_deref(), Some("C:\\icons\\gh.ico"));
+        assert_eq!(shortcut.icon_index, 3);
+        assert_eq!(shortcut.host(), Some("github.com"));
+    }
+
+    #[test]
+    fn internet_shortcut_keys_are_case_insensitive() {
+        let shortcut =
+            parse_url_shortcut("[internetshortcut]\nurl = http://user@intranet/wiki\niconfile=\n")
+                .expect("parse");
+        assert_eq!(shortcut.url, "http://user@intranet/wiki");
+        assert_eq!(shortcut.icon_file, None);
+        assert_eq!(shortcut.host(), Some("intranet"));
+    }
+
+    #[test]
+    fn internet_shortcut_without_url_is_rejected() {
+        assert!(parse_url_shortcut("[InternetShortcut]\nIconIndex=0\n").is_none());
+        assert!(parse_url_shortcut("[Other]\nURL=https://example.com\n").is_none());
+    }
+
+    #[test]
+    fn url_without_authority_has_no_host() {
+        let mailto = UrlShortcut {
+            url: "mailto:someone@example.com".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(mailto.host(), None);
+    }
+
+    #[test]
+    fn decodes_url_file_encodings() {
+        let utf8_bom = b"\xEF\xBB\xBF[InternetShortcut]\nURL=https://example.com/\xE3\x81\x82\n";
+        assert_eq!(decode_text(utf8_bom), "[InternetShortcut]\nURL=https://example.com/あ\n");
+
+        let (sjis, _, _) = encoding_rs::SHIFT_JIS.encode("URL=https://example.com/あ");
+        assert_eq!(decode_text(&sjis), "URL=https://example.com/あ");
+
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend("URL=x".encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(decode_text(&utf16), "URL=x");
+    }
+
+    #[test]
+    fn rejects_non_shortcut_data() {
+        assert!(parse_shortcut(b"").is_none());
+        assert!(parse_shortcut(b"not a shortcut file at all").is_none());
+
+        let mut wrong_clsid = fixture!("unicode_full.lnk").to_vec();
+        wrong_clsid[4] ^= 0xFF;
+        assert!(parse_shortcut(&wrong_clsid).is_none());
+    }
+
+    #[test]
+    fn truncated_shortcut_is_rejected_without_panicking() {
+        let full = fixture!("unicode_full.lnk");
+        for len in 0..full.len() - 4 {
+            let _ = parse_shortcut(&full[..len]);
+        }
+        assert!(parse_shortcut(&full[..100]).is_none());
+    }
+}
@@ -0,0 +1,360 @@
+use std::path::{Path, PathBuf};
+
+use crate::indexer::{AppEntry, EntryKind};
+use crate::shortcut::UrlShortcut;
+
+/// Steamworks Common Redistributables: installed alongside most games, not launchable.
+const REDISTRIBUTABLES_APP_ID: &str = "228980";
+/// `StateFlags` bit set once an app is fully installed.
+const STATE_FULLY_INSTALLED: u32 = 4;
+
+/// A value in Valve's KeyValues text format (`.vdf`, `.acf`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyValue {
+    Text(String),
+    Section(Vec<(String, KeyValue)>),
+}
+
+impl KeyValue {
+    /// First child named `key`. Keys are case-insensitive, as in Steam.
+    pub fn get(&self, key: &str) -> Option<&KeyValue> {
+        self.children()
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) => Some(text),
+            Self::Section(_) => None,
+        }
+    }
+
+    pub fn children(&self) -> &[(String, KeyValue)] {
+        match self {
+            Self::Text(_) => &[],
+            Self::Section(children) => children,
+        }
+    }
+}
+
+/// Parse a KeyValues document into its root key and value.
+pub fn parse_key_values(text: &str) -> Option<(String, KeyValue)> {
+    let mut tokens = Tokens { rest: text };
+    let Some(Token::Text(key)) = tokens.next() else {
+        return None;
+    };
+    let value = parse_value(&mut tokens)?;
+    Some((key, value))
+}
+
+enum Token {
+    Text(String),
+    Open,
+    Close,
+}
+
+struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        loop {
+            self.rest = self.rest.trim_start();
+            match self.rest.strip_prefix("//") {
+                Some(comment) => self.rest = comment.split_once('\n').map_or("", |(_, r)| r),
+                None => break,
+            }
+        }
+        let mut chars = self.rest.chars();
+        let token = match chars.next()? {
+            '{' => Token::Open,
+            '}' => Token::Close,
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        None | Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some(c) => text.push(c),
+                            None => break,
+                        },
+                        Some(c) => text.push(c),
+                    }
+                }
+                self.rest = chars.as_str();
+                return Some(Token::Text(text));
+            }
+            _ => {
+                let end = self
+                    .rest
+                    .find(|c: char| c.is_whitespace() || matches!(c, '{' | '}' | '"'))
+                    .unwrap_or(self.rest.len());
+                let text = self.rest[..end].to_string();
+                self.rest = &self.rest[end..];
+                return Some(Token::Text(text));
+            }
+        };
+        self.rest = chars.as_str();
+        Some(token)
+    }
+}
+
+fn parse_value(tokens: &mut Tokens) -> Option<KeyValue> {
+    match tokens.next()? {
+        Token::Text(text) => Some(KeyValue::Text(text)),
+        Token::Open => {
+            let mut children = Vec::new();
+            loop {
+                match tokens.next() {
+                    Some(Token::Text(key)) => children.push((key, parse_value(tokens)?)),
+                    // An unterminated section keeps what was read.
+                    Some(Token::Close) | None => break,
+                    Some(Token::Open) => return None,
+                }
+            }
+            Some(KeyValue::Section(children))
+        }
+        Token::Close => None,
+    }
+}
+
+/// List of the installation's libraries, including the installation itself.
+pub fn library_folders_file(steam_dir: &Path) -> PathBuf {
+    steamapps_dir(steam_dir).join("libraryfolders.vdf")
+}
+
+/// Folder holding the `appmanifest_*.acf` files of a library.
+pub fn steamapps_dir(library: &Path) -> PathBuf {
+    library.join("steamapps")
+}
+
+/// Library folders of the Steam installation at `steam_dir`, the installation itself first.
+pub fn library_folders(steam_dir: &Path) -> Vec<PathBuf> {
+    let mut libraries = vec![steam_dir.to_path_buf()];
+    let text = std::fs::read_to_string(library_folders_file(steam_dir)).unwrap_or_default();
+    for path in parse_library_folders(&text) {
+        let path = PathBuf::from(path);
+        if !libraries.iter().any(|l| same_path(l, &path)) {
+            libraries.push(path);
+        }
+    }
+    libraries
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    let key = |p: &Path| {
+        p.to_string_lossy()
+            .trim_end_matches(['\\', '/'])
+            .replace('/', "\\")
+            .to_lowercase()
+    };
+    key(a) == key(b)
+}
+
+/// Library paths listed in `libraryfolders.vdf`, in both the current layout
+/// (`"0" { "path" "..." }`) and the older one (`"1" "D:\\SteamLibrary"`).
+pub fn parse_library_folders(text: &str) -> Vec<String> {
+    let Some((_, root)) = parse_key_values(text) else {
+        return Vec::new();
+    };
+    root.children()
+        .iter()
+        .filter(|(key, _)| key.parse::<u32>().is_ok())
+        .filter_map(|(_, value)| match value {
+            KeyValue::Text(path) => Some(path.as_str()),
+            KeyValue::Section(_) => value.get("path").and_then(KeyValue::as_str),
+        })
+        .filter(|path| !path.trim().is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Installed games and tools of every library, in library order and then by manifest name.
+pub fn read_libraries(libraries: &[PathBuf]) -> Vec<AppEntry> {
+    libraries.iter().flat_map(|l| read_library(l)).collect()
+}
+
+fn read_library(library: &Path) -> Vec<AppEntry> {
+    let Ok(read_dir) = std::fs::read_dir(steamapps_dir(library)) else {
+        return Vec::new();
+    };
+    let mut manifests: Vec<PathBuf> = read_dir
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name().and_then(|n| n.to_str()).is_some_and(|n| {
+                let n = n.to_ascii_lowercase();
+                n.starts_with("appmanifest_") && n.ends_with(".acf")
+            })
+        })
+        .collect();
+    manifests.sort();
+    manifests
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter_map(|text| parse_app_manifest(&text))
+        .collect()
+}
+
+/// An installed app from an `appmanifest_<appid>.acf` file, launched through
+/// `steam://rungameid/<appid>`. None for redistributables and unfinished installs.
+pub fn parse_app_manifest(text: &str) -> Option<AppEntry> {
+    let (_, state) = parse_key_values(text)?;
+    let field = |key: &str| state.get(key).and_then(KeyValue::as_str).map(str::trim);
+    let app_id =
+        field("appid").filter(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()))?;
+    let name = field("name").filter(|name| !name.is_empty())?;
+    if app_id == REDISTRIBUTABLES_APP_ID {
+        return None;
+    }
+    if let Some(flags) = field("StateFlags").and_then(|f| f.parse::<u32>().ok())
+        && flags & STATE_FULLY_INSTALLED == 0
+    {
+        return None;
+    }
+    let url = format!("steam://rungameid/{}", app_id);
+    Some(AppEntry {
+        name: name.to_string(),
+        target_path: url.clone(),
+        kind: EntryKind::Url,
+        url: Some(UrlShortcut {
+            url,
+            ..Default::default()
+        }),
+        context: Some("Steam".to_string()),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const LIBRARY_FOLDERS: &str = r#"
+"libraryfolders"
+{
+	"0"
+	{
+		"path"		"C:\\Program Files (x86)\\Steam"
+		"label"		""
+		"apps"
+		{
+			"440"		"24034854302"
+			"228980"		"310412434"
+		}
+	}
+	"1"
+	{
+		"path"		"D:\\SteamLibrary"
+		"apps"
+		{
+			"620"		"12894857341"
+		}
+	}
+}
+"#;
+
+    fn manifest(app_id: &str, name: &str, flags: &str) -> String {
+        format!(
+            "\"AppState\"\n{{\n\t\"appid\"\t\t\"{}\"\n\t\"Universe\"\t\t\"1\"\n\t\"name\"\t\t\"{}\"\n\t\"StateFlags\"\t\t\"{}\"\n\t\"installdir\"\t\t\"{}\"\n\t\"UserConfig\"\n\t{{\n\t\t\"language\"\t\t\"japanese\"\n\t}}\n}}\n",
+            app_id, name, flags, name
+        )
+    }
+
+    #[test]
+    fn parses_key_values_with_escapes_and_comments() {
+        let text = "// generated\n\"Root\" { \"a\" \"x\\\\y \\\"q\\\"\" Sub { \"B\" 2 } }";
+        let (key, root) = parse_key_values(text).expect("parse");
+        assert_eq!(key, "Root");
+        assert_eq!(root.get("a").and_then(KeyValue::as_str), Some("x\\y \"q\""));
+        let sub = root.get("sub").expect("section");
+        assert_eq!(sub.get("b").and_then(KeyValue::as_str), Some("2"));
+        assert!(parse_key_values("").is_none());
+        assert!(parse_key_values("{ }").is_none());
+    }
+
+    #[test]
+    fn parses_both_library_folder_layouts() {
+        assert_eq!(
+            parse_library_folders(LIBRARY_FOLDERS),
+            vec!["C:\\Program Files (x86)\\Steam", "D:\\SteamLibrary"]
+        );
+        let legacy = "\"LibraryFolders\"\n{\n\t\"TimeNextStatsReport\"\t\t\"1700000000\"\n\t\"ContentStatsID\"\t\t\"-1234\"\n\t\"1\"\t\t\"E:\\\\Games\\\\Steam\"\n}\n";
+        assert_eq!(parse_library_folders(legacy), vec!["E:\\Games\\Steam"]);
+    }
+
+    #[test]
+    fn app_manifest_becomes_rungameid_entry() {
+        let entry = parse_app_manifest(&manifest("440", "Team Fortress 2", "4")).expect("entry");
+        assert_eq!(entry.name, "Team Fortress 2");
+        assert_eq!(entry.target_path, "steam://rungameid/440");
+        assert_eq!(entry.launch_target(), "steam://rungameid/440");
+        assert_eq!(entry.kind, EntryKind::Url);
+        assert_eq!(entry.context.as_deref(), Some("Steam"));
+    }
+
+    #[test]
+    fn skips_redistributables_and_unfinished_installs() {
+        assert!(
+            parse_app_manifest(&manifest(
+                "228980",
+                "Steamworks Common Redistributables",
+                "4"
+            ))
+            .is_none()
+        );
+        assert!(parse_app_manifest(&manifest("620", "Portal 2", "1026")).is_none());
+        assert!(parse_app_manifest(&manifest("620", "Portal 2", "6")).is_some());
+        assert!(parse_app_manifest(&manifest("abc", "Broken", "4")).is_none());
+        assert!(parse_app_manifest(&manifest("620", "", "4")).is_none());
+    }
+
+    #[test]
+    fn reads_manifests_of_every_library() {
+        let dir = std::env::temp_dir().join("snotra_steam_test_libraries");
+        let _ = fs::remove_dir_all(&dir);
+        let steam = dir.join("Steam");
+        let other = dir.join("Library");
+        fs::create_dir_all(steamapps_dir(&steam)).unwrap();
+        fs::create_dir_all(steamapps_dir(&other)).unwrap();
+        let folders = format!(
+            "\"libraryfolders\" {{ \"0\" {{ \"path\" \"{}\" }} \"1\" {{ \"path\" \"{}\" }} }}",
+            steam.to_string_lossy().replace('\\', "\\\\"),
+            other.to_string_lossy().replace('\\', "\\\\"),
+        );
+        fs::write(library_folders_file(&steam), folders).unwrap();
+        fs::write(
+            steamapps_dir(&steam).join("appmanifest_440.acf"),
+            manifest("440", "Team Fortress 2", "4"),
+        )
+        .unwrap();
+        fs::write(
+            steamapps_dir(&other).join("appmanifest_620.acf"),
+            manifest("620", "Portal 2", "4"),
+        )
+        .unwrap();
+        fs::write(
+            steamapps_dir(&other).join("libraryfolder.vdf"),
+            "\"libraryfolder\" {}",
+        )
+        .unwrap();
+
+        let libraries = library_folders(&steam);
+        assert_eq!(libraries, vec![steam.clone(), other.clone()]);
+        let names: Vec<String> = read_libraries(&libraries)
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, vec!["Team Fortress 2", "Portal 2"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
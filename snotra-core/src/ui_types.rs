@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::indexer::EntryKind;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchResult {
@@ -7,6 +9,14 @@ pub struct SearchResult {
     pub path: String,
     pub is_folder: bool,
     pub is_error: bool,
+    /// Shortcut comment or bookmark folder, shown next to the path.
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub kind: EntryKind,
+    /// Char indices of `name` matched by the query, for highlighting.
+    #[serde(default)]
+    pub highlights: Vec<usize>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
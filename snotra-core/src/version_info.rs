@@ -0,0 +1,361 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+// PE/COFF: only the headers, section table and resource tree are read.
+const PE_SIGNATURE: &[u8; 4] = b"PE\0\0";
+const PE32_MAGIC: u16 = 0x10B;
+const PE32_PLUS_MAGIC: u16 = 0x20B;
+const COFF_HEADER_SIZE: u64 = 24;
+const SECTION_HEADER_SIZE: usize = 40;
+const RESOURCE_TABLE_INDEX: usize = 2;
+const RT_VERSION: u32 = 16;
+const SUBDIRECTORY: u32 = 0x8000_0000;
+
+/// Real version resources are a few KB; anything larger is not worth reading.
+const MAX_VERSION_RESOURCE_SIZE: u32 = 64 * 1024;
+const MAX_SECTIONS: usize = 96;
+const MAX_DIRECTORY_ENTRIES: usize = 4096;
+
+/// Names from the `StringFileInfo` of an executable's version resource.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub product_name: Option<String>,
+    pub file_description: Option<String>,
+    pub company_name: Option<String>,
+}
+
+impl VersionInfo {
+    /// Product name, description and company, skipping repeats (case-insensitive).
+    pub fn keywords(&self) -> Vec<String> {
+        let mut keywords: Vec<String> = Vec::new();
+        let fields = [
+            &self.product_name,
+            &self.file_description,
+            &self.company_name,
+        ];
+        for value in fields.into_iter().flatten() {
+            if !keywords
+                .iter()
+                .any(|k| k.to_lowercase() == value.to_lowercase())
+            {
+                keywords.push(value.clone());
+            }
+        }
+        keywords
+    }
+
+    fn is_empty(&self) -> bool {
+        self.product_name.is_none()
+            && self.file_description.is_none()
+            && self.company_name.is_none()
+    }
+}
+
+/// Read the version resource of a PE file without loading the whole image.
+pub fn read_version_info(path: &Path) -> Option<VersionInfo> {
+    let mut file = File::open(path).ok()?;
+    extract(&mut file)
+}
+
+/// Parse the version resource from the bytes of a PE image.
+/// Returns None when the image has no version resource or none of the names.
+pub fn parse_version_info(image: &[u8]) -> Option<VersionInfo> {
+    extract(&mut Cursor::new(image))
+}
+
+fn extract<R: Read + Seek>(reader: &mut R) -> Option<VersionInfo> {
+    let resource = find_version_resource(reader)?;
+    parse_vs_version_info(&resource).filter(|info| !info.is_empty())
+}
+
+struct Section {
+    virtual_address: u32,
+    virtual_size: u32,
+    raw_size: u32,
+    raw_offset: u32,
+}
+
+impl Section {
+    fn file_offset(&self, rva: u32) -> Option<u64> {
+        let delta = rva.checked_sub(self.virtual_address)?;
+        // Only the part backed by file data can be read; the rest is zero-filled in memory.
+        let mapped = match self.virtual_size {
+            0 => self.raw_size,
+            size => size.min(self.raw_size),
+        };
+        (delta < mapped).then(|| self.raw_offset as u64 + delta as u64)
+    }
+}
+
+/// Raw `VS_VERSIONINFO` bytes: the first language of the first `RT_VERSION` resource.
+fn find_version_resource<R: Read + Seek>(reader: &mut R) -> Option<Vec<u8>> {
+    let dos = read_at(reader, 0, 64)?;
+    if !dos.starts_with(b"MZ") {
+        return None;
+    }
+    let pe_offset = read_u32(&dos, 0x3C)? as u64;
+    let coff = read_at(reader, pe_offset, COFF_HEADER_SIZE as usize)?;
+    if !coff.starts_with(PE_SIGNATURE) {
+        return None;
+    }
+    let section_count = (read_u16(&coff, 6)? as usize).min(MAX_SECTIONS);
+    let optional_size = read_u16(&coff, 20)? as usize;
+    let optional = read_at(reader, pe_offset + COFF_HEADER_SIZE, optional_size)?;
+    let data_directories = match read_u16(&optional, 0)? {
+        PE32_MAGIC => 96,
+        PE32_PLUS_MAGIC => 112,
+        _ => return None,
+    };
+    if (read_u32(&optional, data_directories - 4)? as usize) <= RESOURCE_TABLE_INDEX {
+        return None;
+    }
+    let resource_rva = read_u32(&optional, data_directories + RESOURCE_TABLE_INDEX * 8)?;
+    if resource_rva == 0 {
+        return None;
+    }
+
+    let table = read_at(
+        reader,
+        pe_offset + COFF_HEADER_SIZE + optional_size as u64,
+        section_count * SECTION_HEADER_SIZE,
+    )?;
+    let sections: Vec<Section> = table
+        .chunks_exact(SECTION_HEADER_SIZE)
+        .filter_map(|s| {
+            Some(Section {
+                virtual_size: read_u32(s, 8)?,
+                virtual_address: read_u32(s, 12)?,
+                raw_size: read_u32(s, 16)?,
+                raw_offset: read_u32(s, 20)?,
+            })
+        })
+        .collect();
+    let file_offset = |rva: u32| sections.iter().find_map(|s| s.file_offset(rva));
+
+    // Type -> name -> language; offsets inside the tree are relative to its start.
+    let root = file_offset(resource_rva)?;
+    let names = find_resource_entry(reader, root, 0, Some(RT_VERSION))?;
+    let languages = find_resource_entry(reader, root, subdirectory(names)?, None)?;
+    let data_entry = find_resource_entry(reader, root, subdirectory(languages)?, None)?;
+    if data_entry & SUBDIRECTORY != 0 {
+        return None;
+    }
+    let data = read_at(reader, root + data_entry as u64, 8)?;
+    let size = read_u32(&data, 4)?;
+    if size > MAX_VERSION_RESOURCE_SIZE {
+        return None;
+    }
+    read_at(reader, file_offset(read_u32(&data, 0)?)?, size as usize)
+}
+
+fn subdirectory(entry: u32) -> Option<u32> {
+    (entry & SUBDIRECTORY != 0).then_some(entry & !SUBDIRECTORY)
+}
+
+/// `OffsetToData` of the entry with numeric `id` in the directory at `dir`, or of its first
+/// entry when `id` is None.
+fn find_resource_entry<R: Read + Seek>(
+    reader: &mut R,
+    root: u64,
+    dir: u32,
+    id: Option<u32>,
+) -> Option<u32> {
+    let header = read_at(reader, root + dir as u64, 16)?;
+    let named = read_u16(&header, 12)? as usize;
+    let count = (named + read_u16(&header, 14)? as usize).min(MAX_DIRECTORY_ENTRIES);
+    let entries = read_at(reader, root + dir as u64 + 16, count * 8)?;
+    entries.chunks_exact(8).find_map(|entry| {
+        let name = read_u32(entry, 0)?;
+        let matches = match id {
+            Some(id) => name & SUBDIRECTORY == 0 && name == id,
+            None => true,
+        };
+        matches.then(|| read_u32(entry, 4)).flatten()
+    })
+}
+
+/// One node of the version resource tree: `wLength`, `wValueLength`, `wType`, key, value
+/// and children, each aligned to 32 bits.
+struct VersionBlock<'a> {
+    key: String,
+    value: &'a [u8],
+    children: &'a [u8],
+}
+
+impl<'a> VersionBlock<'a> {
+    /// Parse the block at the start of `bytes`; also returns its length.
+    fn parse(bytes: &'a [u8]) -> Option<(Self, usize)> {
+        let length = read_u16(bytes, 0)? as usize;
+        let value_length = read_u16(bytes, 2)? as usize;
+        let is_text = read_u16(bytes, 4)? == 1;
+        let block = bytes.get(..length)?;
+        let key_units = block.get(6..)?.chunks_exact(2).position(|c| c == [0, 0])?;
+        let key = decode_utf16(&block[6..6 + key_units * 2]);
+        let value_start = align4(6 + key_units * 2 + 2).min(length);
+        let value_bytes = if is_text {
+            value_length * 2
+        } else {
+            value_length
+        };
+        let value_end = (value_start + value_bytes).min(length);
+        let children_start = align4(value_end).min(length);
+        Some((
+            Self {
+                key,
+                value: &block[value_start..value_end],
+                children: &block[children_start..],
+            },
+            length,
+        ))
+    }
+
+    fn children(&self) -> impl Iterator<Item = VersionBlock<'a>> {
+        let mut rest = self.children;
+        std::iter::from_fn(move || {
+            let (child, length) = VersionBlock::parse(rest)?;
+            rest = rest.get(align4(length)..).unwrap_or_default();
+            Some(child)
+        })
+    }
+
+    /// The value as a NUL-terminated string, trimmed; None when empty.
+    fn text(&self) -> Option<String> {
+        let len = self
+            .value
+            .chunks_exact(2)
+            .position(|c| c == [0, 0])
+            .unwrap_or(self.value.len() / 2);
+        let text = decode_utf16(&self.value[..len * 2]);
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+}
+
+/// Take each name from the first string table (language) that defines it.
+fn parse_vs_version_info(bytes: &[u8]) -> Option<VersionInfo> {
+    let (root, _) = VersionBlock::parse(bytes)?;
+    if root.key != "VS_VERSION_INFO" {
+        return None;
+    }
+    let mut info = VersionInfo::default();
+    let tables = root
+        .children()
+        .filter(|b| b.key == "StringFileInfo")
+        .flat_map(|b| b.children().collect::<Vec<_>>());
+    for table in tables {
+        for string in table.children() {
+            let field = match string.key.as_str() {
+                "ProductName" => &mut info.product_name,
+                "FileDescription" => &mut info.file_description,
+                "CompanyName" => &mut info.company_name,
+                _ => continue,
+            };
+            if field.is_none() {
+                *field = string.text();
+            }
+        }
+    }
+    Some(info)
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+fn read_at<R: Read + Seek>(reader: &mut R, offset: u64, len: usize) -> Option<Vec<u8>> {
+    reader.seek(SeekFrom::Start(offset)).ok()?;
+    let mut buf = vec![0; len];
+    reader.read_exact(&mut buf).ok()?;
+    Some(buf)
+}
+
+fn decode_utf16(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let b = bytes.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let b = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_bytes!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/pe/",
+                $name
+            ))
+        };
+    }
+
+    #[test]
+    fn reads_version_strings_from_pe32_plus() {
+        let info = parse_version_info(fixture!("winword.exe")).expect("parse");
+        assert_eq!(info.product_name.as_deref(), Some("Microsoft Office"));
+        assert_eq!(info.file_description.as_deref(), Some("Microsoft Word"));
+        assert_eq!(info.company_name.as_deref(), Some("Microsoft Corporation"));
+        assert_eq!(
+            info.keywords(),
+            vec![
+                "Microsoft Office",
+                "Microsoft Word",
+                "Microsoft Corporation"
+            ]
+        );
+    }
+
+    #[test]
+    fn takes_each_name_from_first_table_defining_it() {
+        let info = parse_version_info(fixture!("viewer_pe32.exe")).expect("parse");
+        assert_eq!(info.product_name.as_deref(), Some("画像ビューア"));
+        assert_eq!(info.file_description.as_deref(), Some("画像ビューア"));
+        assert_eq!(info.company_name.as_deref(), Some("サンプル株式会社"));
+        assert_eq!(info.keywords(), vec!["画像ビューア", "サンプル株式会社"]);
+    }
+
+    #[test]
+    fn reads_version_info_from_file() {
+        let dir = std::env::temp_dir().join("snotra_pe_test_file");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("WINWORD.EXE");
+        std::fs::write(&exe, fixture!("winword.exe")).unwrap();
+
+        let info = read_version_info(&exe).expect("read");
+        assert_eq!(info.file_description.as_deref(), Some("Microsoft Word"));
+        assert_eq!(read_version_info(&dir.join("missing.exe")), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn image_without_resources_has_no_version_info() {
+        assert_eq!(parse_version_info(fixture!("no_resources.exe")), None);
+        assert_eq!(parse_version_info(b""), None);
+        assert_eq!(parse_version_info(b"MZ not a portable executable"), None);
+    }
+
+    #[test]
+    fn truncated_image_is_rejected_without_panicking() {
+        let full = fixture!("winword.exe");
+        for len in 0..full.len() {
+            let _ = parse_version_info(&full[..len]);
+        }
+        assert_eq!(parse_version_info(&full[..0x200]), None);
+    }
+}
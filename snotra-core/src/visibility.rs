@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::Path;
+
+/// Decides which directory entries are hidden from the index and folder listings.
+pub trait VisibilityPolicy: Send + Sync {
+    /// Returns true if the entry itself is marked hidden.
+    fn is_hidden(&self, path: &Path) -> bool;
+
+    /// Names hidden by a per-directory listing (e.g. a `.hidden` file).
+    fn hidden_names(&self, _dir: &Path) -> HashSet<OsString> {
+        HashSet::new()
+    }
+}
+
+/// Shows every entry (`show_hidden_system = true`).
+pub struct ShowAll;
+
+impl VisibilityPolicy for ShowAll {
+    fn is_hidden(&self, _path: &Path) -> bool {
+        false
+    }
+}
+
+/// Windows hidden/system file attributes.
+#[cfg(windows)]
+pub struct FileAttributes;
+
+#[cfg(windows)]
+impl VisibilityPolicy for FileAttributes {
+    fn is_hidden(&self, path: &Path) -> bool {
+        use std::os::windows::fs::MetadataExt;
+        use windows::Win32::Storage::FileSystem::{FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_SYSTEM};
+
+        let Ok(meta) = std::fs::metadata(path) else {
+            return false;
+        };
+        let attrs = meta.file_attributes();
+        let hidden = (attrs & FILE_ATTRIBUTE_HIDDEN.0) != 0;
+        let system = (attrs & FILE_ATTRIBUTE_SYSTEM.0) != 0;
+        hidden || system
+    }
+}
+
+/// Unix conventions: dot-prefixed names and names listed in the directory's `.hidden` file.
+pub struct Dotfiles;
+
+impl VisibilityPolicy for Dotfiles {
+    fn is_hidden(&self, path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+    }
+
+    fn hidden_names(&self, dir: &Path) -> HashSet<OsString> {
+        let Ok(content) = std::fs::read_to_string(dir.join(".hidden")) else {
+            return HashSet::new();
+        };
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(OsString::from)
+            .collect()
+    }
+}
+
+/// Returns the native policy for this platform, or `ShowAll` when hidden items are shown.
+pub fn policy_for(show_hidden_system: bool) -> &'static dyn VisibilityPolicy {
+    if show_hidden_system {
+        return &ShowAll;
+    }
+    #[cfg(windows)]
+    {
+        &FileAttributes
+    }
+    #[cfg(not(windows))]
+    {
+        &Dotfiles
+    }
+}
+
+/// A policy bound to one directory, so per-directory listings are read only once.
+pub struct DirVisibility<'a> {
+    policy: &'a dyn VisibilityPolicy,
+    hidden_names: HashSet<OsString>,
+}
+
+impl<'a> DirVisibility<'a> {
+    pub fn new(policy: &'a dyn VisibilityPolicy, dir: &Path) -> Self {
+        Self {
+            policy,
+            hidden_names: policy.hidden_names(dir),
+        }
+    }
+
+    pub fn is_visible(&self, path: &Path) -> bool {
+        if let Some(name) = path.file_name()
+            && self.hidden_names.contains(name)
+        {
+            return false;
+        }
+        !self.policy.is_hidden(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_dir(tag: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("snotra_vis_test_{}", tag));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn show_all_hides_nothing() {
+        let dir = temp_dir("show_all");
+        fs::write(dir.join(".secret"), "").unwrap();
+        fs::write(dir.join(".hidden"), "plain.txt\n").unwrap();
+
+        let vis = DirVisibility::new(&ShowAll, &dir);
+        assert!(vis.is_visible(&dir.join(".secret")));
+        assert!(vis.is_visible(&dir.join("plain.txt")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dotfiles_hides_dot_prefixed_names() {
+        let dir = temp_dir("dot_prefix");
+        let vis = DirVisibility::new(&Dotfiles, &dir);
+        assert!(!vis.is_visible(&dir.join(".git")));
+        assert!(vis.is_visible(&dir.join("app.exe")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dotfiles_honours_hidden_listing() {
+        let dir = temp_dir("hidden_listing");
+        fs::write(dir.join(".hidden"), "build\n  cache.db  \n\n").unwrap();
+
+        let vis = DirVisibility::new(&Dotfiles, &dir);
+        assert!(!vis.is_visible(&dir.join("build")));
+        assert!(!vis.is_visible(&dir.join("cache.db")));
+        assert!(vis.is_visible(&dir.join("src")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_hidden_listing_is_empty() {
+        let dir = temp_dir("no_listing");
+        assert!(Dotfiles.hidden_names(&dir).is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
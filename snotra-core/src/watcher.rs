@@ -0,0 +1,370 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::config::PathsConfig;
+use crate::indexer::{self, RescanResult};
+
+/// Events are flushed at the latest after this many debounce periods of continuous activity.
+const MAX_DEBOUNCE_PERIODS: u32 = 10;
+
+#[derive(Debug, Clone, Copy)]
+pub struct WatchOptions {
+    /// Quiet period after the last event before changes are applied.
+    pub debounce: Duration,
+    /// Interval of incremental rescans of the scan roots and bookmark files that cannot be
+    /// watched.
+    pub fallback_interval: Duration,
+}
+
+enum WatchMessage {
+    Paths(Vec<PathBuf>),
+    Resync,
+    Stop,
+}
+
+/// Watches all scan roots and bookmark files and keeps the index cache up to date.
+/// Stops when dropped, waiting for the worker so it no longer writes the cache.
+pub struct IndexWatcher {
+    _watcher: Option<RecommendedWatcher>,
+    tx: Sender<WatchMessage>,
+    worker: Option<JoinHandle<()>>,
+    /// Scan roots and bookmark files that could not be watched and are polled instead.
+    unwatched: Vec<PathBuf>,
+}
+
+impl IndexWatcher {
+    /// Start watching. `on_update` is called from the worker thread whenever the index changed.
+    /// Roots and bookmark files that cannot be watched are rescanned periodically instead.
+    pub fn start<F>(
+        paths: PathsConfig,
+        show_hidden_system: bool,
+        options: WatchOptions,
+        on_update: F,
+    ) -> Self
+    where
+        F: Fn(RescanResult) + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+
+        let roots: Vec<PathBuf> = paths
+            .scan
+            .iter()
+            .map(|sp| PathBuf::from(sp.expanded_path()))
+            .collect();
+        let bookmark_files: Vec<PathBuf> = paths
+            .bookmarks
+            .iter()
+            .map(|b| PathBuf::from(b.expanded_path()))
+            .collect();
+        let filter = EventFilter::new(roots.clone(), bookmark_files.clone());
+
+        let event_tx = tx.clone();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let msg = match res {
+                Ok(event) if event.need_rescan() => WatchMessage::Resync,
+                Ok(event) => match filter.relevant_paths(&event) {
+                    Some(paths) => WatchMessage::Paths(paths),
+                    None => return,
+                },
+                Err(_) => WatchMessage::Resync,
+            };
+            let _ = event_tx.send(msg);
+        })
+        .ok();
+
+        let mut unwatched: Vec<PathBuf> = Vec::new();
+        for root in &roots {
+            let watched = watcher
+                .as_mut()
+                .is_some_and(|w| w.watch(root, RecursiveMode::Recursive).is_ok());
+            if !watched {
+                unwatched.push(root.clone());
+            }
+        }
+        // Browsers replace the bookmarks file, so watch its folder rather than the file.
+        for file in &bookmark_files {
+            let watched = watcher.as_mut().is_some_and(|w| {
+                file.parent()
+                    .is_some_and(|dir| w.watch(dir, RecursiveMode::NonRecursive).is_ok())
+            });
+            if !watched {
+                unwatched.push(file.clone());
+            }
+        }
+
+        let polled = unwatched.clone();
+        let worker = thread::Builder::new()
+            .name("snotra-index-watch".to_string())
+            .spawn(move || {
+                let mut pending: HashSet<PathBuf> = HashSet::new();
+                let mut resync = false;
+                let mut first_pending: Option<Instant> = None;
+                let max_wait = options.debounce * MAX_DEBOUNCE_PERIODS;
+                let mut next_poll =
+                    (!polled.is_empty()).then(|| Instant::now() + options.fallback_interval);
+
+                loop {
+                    let flush_in = first_pending.map(|started| {
+                        options
+                            .debounce
+                            .min(max_wait.saturating_sub(started.elapsed()))
+                    });
+                    let poll_in = next_poll.map(|at| at.saturating_duration_since(Instant::now()));
+                    let timeout = match (flush_in, poll_in) {
+                        (Some(a), Some(b)) => a.min(b),
+                        (a, b) => a.or(b).unwrap_or(Duration::MAX),
+                    };
+
+                    match rx.recv_timeout(timeout) {
+                        Ok(WatchMessage::Paths(paths)) => {
+                            pending.extend(paths);
+                            first_pending.get_or_insert_with(Instant::now);
+                        }
+                        Ok(WatchMessage::Resync) => {
+                            resync = true;
+                            first_pending.get_or_insert_with(Instant::now);
+                        }
+                        Ok(WatchMessage::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+                        Err(RecvTimeoutError::Timeout) => {
+                            let result = if next_poll.is_some_and(|at| Instant::now() >= at) {
+                                next_poll = Some(Instant::now() + options.fallback_interval);
+                                indexer::rescan_unwatched(&paths, show_hidden_system, &polled)
+                            } else {
+                                let result = if resync {
+                                    indexer::rescan_cached(&paths, show_hidden_system)
+                                } else {
+                                    let changed: Vec<PathBuf> = pending.iter().cloned().collect();
+                                    indexer::apply_changes(&paths, show_hidden_system, &changed)
+                                };
+                                pending.clear();
+                                resync = false;
+                                first_pending = None;
+                                result
+                            };
+                            if let Some(result) = result
+                                && !result.diff.is_empty()
+                            {
+                                on_update(result);
+                            }
+                        }
+                    }
+                }
+            });
+
+        Self {
+            _watcher: watcher,
+            tx,
+            worker: worker.ok(),
+            unwatched,
+        }
+    }
+
+    /// False when some root or bookmark file could not be watched and is polled instead.
+    pub fn is_watching(&self) -> bool {
+        self.unwatched.is_empty()
+    }
+
+    /// Roots and bookmark files that are polled instead of watched.
+    pub fn unwatched(&self) -> &[PathBuf] {
+        &self.unwatched
+    }
+}
+
+impl Drop for IndexWatcher {
+    fn drop(&mut self) {
+        let _ = self.tx.send(WatchMessage::Stop);
+        // An update in progress finishes first, so a new watcher never writes the cache
+        // at the same time.
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Paths touched by a create, remove or rename event; other events are ignored.
+fn event_paths(event: &Event) -> Option<Vec<PathBuf>> {
+    match event.kind {
+        EventKind::Create(_)
+        | EventKind::Remove(_)
+        | EventKind::Modify(ModifyKind::Name(_))
+        | EventKind::Any => Some(event.paths.clone()),
+        _ => None,
+    }
+}
+
+/// Keeps events under a scan root or on a bookmark file. Bookmark folders are browser
+/// profile folders with constant unrelated activity, so everything else there is dropped.
+struct EventFilter {
+    roots: Vec<PathBuf>,
+    bookmark_keys: HashSet<String>,
+}
+
+impl EventFilter {
+    fn new(roots: Vec<PathBuf>, bookmark_files: Vec<PathBuf>) -> Self {
+        Self {
+            roots,
+            bookmark_keys: bookmark_files
+                .iter()
+                .map(|f| indexer::normalize_entry_key(&f.to_string_lossy()))
+                .collect(),
+        }
+    }
+
+    fn is_bookmark_file(&self, path: &Path) -> bool {
+        self.bookmark_keys
+            .contains(&indexer::normalize_entry_key(&path.to_string_lossy()))
+    }
+
+    fn relevant_paths(&self, event: &Event) -> Option<Vec<PathBuf>> {
+        let paths: Vec<PathBuf> = match event_paths(event) {
+            Some(paths) => paths
+                .into_iter()
+                .filter(|p| self.roots.iter().any(|r| p.starts_with(r)) || self.is_bookmark_file(p))
+                .collect(),
+            // Bookmark files may also be rewritten in place.
+            None if matches!(event.kind, EventKind::Modify(_)) => event
+                .paths
+                .iter()
+                .filter(|p| self.is_bookmark_file(p))
+                .cloned()
+                .collect(),
+            None => return None,
+        };
+        (!paths.is_empty()).then_some(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, ScanPath};
+    use notify::event::{CreateKind, DataChange, RenameMode};
+
+    fn event(kind: EventKind, paths: &[&str]) -> Event {
+        let mut e = Event::new(kind);
+        for p in paths {
+            e = e.add_path(PathBuf::from(p));
+        }
+        e
+    }
+
+    #[test]
+    fn create_and_rename_events_are_kept() {
+        let created = event(EventKind::Create(CreateKind::File), &["C:\\a\\new.lnk"]);
+        assert_eq!(
+            event_paths(&created),
+            Some(vec![PathBuf::from("C:\\a\\new.lnk")])
+        );
+
+        let renamed = event(
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+            &["C:\\a\\old.lnk", "C:\\a\\new.lnk"],
+        );
+        assert_eq!(event_paths(&renamed).map(|p| p.len()), Some(2));
+    }
+
+    #[test]
+    fn content_changes_are_ignored() {
+        let modified = event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            &["C:\\a\\app.lnk"],
+        );
+        assert!(event_paths(&modified).is_none());
+    }
+
+    #[test]
+    fn filter_keeps_scan_roots_and_bookmark_files_only() {
+        let filter = EventFilter::new(
+            vec![PathBuf::from("/menu")],
+            vec![PathBuf::from("/profile/Bookmarks")],
+        );
+        let created = event(
+            EventKind::Create(CreateKind::File),
+            &["/menu/app.lnk", "/profile/History-journal", "/elsewhere/x.lnk"],
+        );
+        assert_eq!(
+            filter.relevant_paths(&created),
+            Some(vec![PathBuf::from("/menu/app.lnk")])
+        );
+
+        let rewritten = event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            &["/profile/Bookmarks"],
+        );
+        assert_eq!(
+            filter.relevant_paths(&rewritten),
+            Some(vec![PathBuf::from("/profile/Bookmarks")])
+        );
+
+        let noise = event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            &["/profile/History", "/menu/app.lnk"],
+        );
+        assert_eq!(filter.relevant_paths(&noise), None);
+    }
+
+    #[test]
+    fn unwatchable_root_falls_back_to_polling() {
+        let scan = vec![ScanPath {
+            path: std::env::temp_dir()
+                .join("snotra_watch_test_missing_root")
+                .to_string_lossy()
+                .to_string(),
+            extensions: vec![".lnk".to_string()],
+            include_folders: false,
+            ..Default::default()
+        }];
+        let paths = PathsConfig {
+            scan,
+            ..Config::default().paths
+        };
+        let watcher = IndexWatcher::start(
+            paths,
+            false,
+            WatchOptions {
+                debounce: Duration::from_millis(50),
+                fallback_interval: Duration::from_secs(3600),
+            },
+            |_| {},
+        );
+        assert!(!watcher.is_watching());
+    }
+
+    #[test]
+    fn only_unwatchable_roots_are_polled() {
+        let watched_root = std::env::temp_dir().join("snotra_watch_test_partial_root");
+        let _ = std::fs::create_dir_all(&watched_root);
+        let missing_root = std::env::temp_dir().join("snotra_watch_test_partial_missing");
+        let scan = [&watched_root, &missing_root]
+            .iter()
+            .map(|root| ScanPath {
+                path: root.to_string_lossy().to_string(),
+                extensions: vec![".lnk".to_string()],
+                ..Default::default()
+            })
+            .collect();
+        let paths = PathsConfig {
+            scan,
+            bookmarks: Vec::new(),
+            ..Config::default().paths
+        };
+        let watcher = IndexWatcher::start(
+            paths,
+            false,
+            WatchOptions {
+                debounce: Duration::from_millis(50),
+                fallback_interval: Duration::from_secs(3600),
+            },
+            |_| {},
+        );
+        assert_eq!(watcher.unwatched(), [missing_root]);
+        drop(watcher);
+        let _ = std::fs::remove_dir_all(&watched_root);
+    }
+}
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 
 use crate::binfmt::{deserialize_with_header, serialize_with_header};
 use crate::config::Config;
+use crate::export::ImportError;
 
 const WINDOW_MAGIC: [u8; 4] = *b"WNDW";
 const WINDOW_VERSION_V1: u32 = 1;
@@ -64,6 +65,31 @@ pub fn save_settings_size(size: WindowSize) {
     save_state(&state);
 }
 
+/// Saved window positions and sizes as JSON; None when nothing has been saved yet.
+pub fn export_json() -> Option<String> {
+    serde_json::to_string_pretty(&load_state()?).ok()
+}
+
+/// Replace the saved window positions and sizes with an `export_json` document.
+pub fn import_json(json: &str) -> Result<(), ImportError> {
+    let state = parse_state_json(json)?;
+    save_state(&state);
+    Ok(())
+}
+
+fn parse_state_json(json: &str) -> Result<WindowPlacementState, ImportError> {
+    let state: WindowPlacementState = serde_json::from_str(json)?;
+    if let Some(size) = state.settings_size
+        && (size.width <= 0 || size.height <= 0)
+    {
+        return Err(ImportError::Invalid(format!(
+            "設定ウィンドウのサイズが不正です: {}x{}",
+            size.width, size.height
+        )));
+    }
+    Ok(state)
+}
+
 fn load_state() -> Option<WindowPlacementState> {
     let path = path()?;
     let bytes = std::fs::read(path).ok()?;
@@ -136,6 +162,30 @@ mod tests {
         assert_eq!(state, restored);
     }
 
+    #[test]
+    fn placement_state_json_roundtrip_and_validation() {
+        let state = WindowPlacementState {
+            search: Some(WindowPlacement { x: -20, y: 340 }),
+            settings: None,
+            settings_size: Some(WindowSize {
+                width: 760,
+                height: 560,
+            }),
+        };
+        let json = serde_json::to_string_pretty(&state).expect("serialize");
+        assert_eq!(parse_state_json(&json).expect("valid"), state);
+
+        let broken = json.replace("760", "0");
+        assert!(matches!(
+            parse_state_json(&broken),
+            Err(ImportError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse_state_json("{\"search\": 1}"),
+            Err(ImportError::Parse(_))
+        ));
+    }
+
     #[test]
     fn load_state_reads_v2_payload() {
         let state = WindowPlacementStateV2 {
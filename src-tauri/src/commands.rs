@@ -1,12 +1,16 @@
 use std::path::Path;
 use std::sync::atomic::Ordering;
 
-use snotra_core::config::Config;
+use snotra_core::config::{self, Config};
+use snotra_core::diagnostics::{self, ScanDiagnostics};
+use snotra_core::export::{self, ImportError};
 use snotra_core::folder;
+use snotra_core::history::HistoryImportMode;
 use snotra_core::search::SearchMode;
 use snotra_core::ui_types::SearchResult;
 use snotra_core::window_data::{self, WindowPlacement, WindowSize};
 use tauri::{AppHandle, Emitter, LogicalSize, Manager, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 
 use crate::icon::IconCacheState;
 use crate::indexing;
@@ -44,16 +48,35 @@ pub fn launch_item(path: String, query: String, state: State<AppState>) {
     }
     #[cfg(windows)]
     {
-        use windows::core::HSTRING;
+        use windows::core::{HSTRING, PCWSTR};
         use windows::Win32::UI::Shell::ShellExecuteW;
         use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+        // URL entries open their URL; custom entries run their target with arguments
+        // and working directory; everything else opens the path itself
+        let (target, args, working_dir) = {
+            let engine = state.engine.lock().unwrap();
+            match engine.entries().iter().find(|e| e.target_path == path) {
+                Some(e) if e.custom && e.url.is_none() => {
+                    let launch = e.shortcut.clone().unwrap_or_default();
+                    (
+                        launch.target.unwrap_or_else(|| path.clone()),
+                        launch.arguments,
+                        launch.working_dir,
+                    )
+                }
+                Some(e) => (e.launch_target().to_string(), None, None),
+                None => (path.clone(), None, None),
+            }
+        };
+        let args = args.map(|a| HSTRING::from(&a));
+        let working_dir = working_dir.map(|d| HSTRING::from(&d));
         unsafe {
             ShellExecuteW(
                 None,
                 &HSTRING::from("open"),
-                &HSTRING::from(&path),
-                None,
-                None,
+                &HSTRING::from(&target),
+                args.as_ref().map_or(PCWSTR::null(), |a| PCWSTR(a.as_ptr())),
+                working_dir.as_ref().map_or(PCWSTR::null(), |d| PCWSTR(d.as_ptr())),
                 SW_SHOWNORMAL,
             );
         }
@@ -94,9 +117,23 @@ pub fn save_config(
     config.save();
 
     // Detect what changed before moving config into state
-    let index_changed = config.paths.scan != old_config.paths.scan
+    let scan_changed = config.paths.scan.len() != old_config.paths.scan.len()
+        || config
+            .paths
+            .scan
+            .iter()
+            .zip(&old_config.paths.scan)
+            .any(|(new, old)| !new.scans_same_as(old));
+    let weights_changed = !scan_changed && config.paths.scan != old_config.paths.scan;
+    let index_changed = scan_changed
+        || config.paths.bookmarks != old_config.paths.bookmarks
+        || config.paths.steam_dir != old_config.paths.steam_dir
+        || config.paths.dedup_targets != old_config.paths.dedup_targets
         || config.search.show_hidden_system != old_config.search.show_hidden_system
         || config.appearance.show_icons != old_config.appearance.show_icons;
+    let custom_changed = config.entries != old_config.entries;
+    let watch_changed = config.paths.watch != old_config.paths.watch
+        || config.paths.rescan_interval_minutes != old_config.paths.rescan_interval_minutes;
     let visual_changed = config.visual != old_config.visual;
     let width_changed = config.appearance.window_width != old_config.appearance.window_width;
     let new_visual = if visual_changed {
@@ -153,6 +190,21 @@ pub fn save_config(
         reindex_started = indexing::start_index_build(&app);
     }
 
+    // Weights alone only re-rank the cached entries
+    if weights_changed && !reindex_started && !indexing_in_progress {
+        indexing::apply_scan_weights(&app);
+    }
+
+    // A running build picks up custom entries when it completes
+    if custom_changed && !reindex_started {
+        indexing::apply_custom_entries(&app);
+    }
+
+    // A running build restarts the watcher when it completes
+    if watch_changed && !state.indexing.load(Ordering::SeqCst) {
+        indexing::restart_watcher(&app);
+    }
+
     // Emit visual config change for live theme update
     if let Some(visual) = new_visual {
         let _ = app.emit("visual-config-changed", &visual);
@@ -289,6 +341,162 @@ pub fn rebuild_index(state: State<AppState>, app: AppHandle) -> bool {
     indexing::start_index_build(&app)
 }
 
+/// Stop a running index build; the previous index stays in use.
+#[tauri::command]
+pub fn cancel_index_build(state: State<AppState>) -> bool {
+    match state.index_cancel.lock().unwrap().as_ref() {
+        Some(cancel) => {
+            cancel.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Write the index, history and window stores as JSON into the export folder and open it.
+#[tauri::command]
+pub fn export_data(state: State<AppState>, app: AppHandle) {
+    let Some(dir) = export::export_dir() else {
+        return;
+    };
+    let result = {
+        let history = state.history.lock().unwrap();
+        export::export_all(&dir, &history)
+    };
+    match result {
+        Ok(_) => open_in_shell(&dir.to_string_lossy()),
+        Err(e) => show_message(
+            &app,
+            MessageDialogKind::Error,
+            format!("書き出しに失敗しました: {}", e),
+        ),
+    }
+}
+
+/// Import the JSON files found in the export folder. History is merged into the current
+/// one unless `replace_history` is set; the index is only accepted for the current settings.
+#[tauri::command]
+pub fn import_data(replace_history: bool, state: State<AppState>, app: AppHandle) {
+    let Some(dir) = export::export_dir() else {
+        return;
+    };
+    // A running build would overwrite the imported index
+    if state.indexing.load(Ordering::SeqCst) {
+        show_message(
+            &app,
+            MessageDialogKind::Warning,
+            "インデックス構築中は取り込めません".to_string(),
+        );
+        return;
+    }
+    let (paths, show_hidden_system, show_icons) = {
+        let config = state.config.lock().unwrap();
+        (
+            config.paths.clone(),
+            config.search.show_hidden_system,
+            config.appearance.show_icons,
+        )
+    };
+    let mode = if replace_history {
+        HistoryImportMode::Replace
+    } else {
+        HistoryImportMode::Merge
+    };
+    let report = {
+        let mut history = state.history.lock().unwrap();
+        export::import_all(&dir, &paths, show_hidden_system, &mut history, mode)
+    };
+
+    let mut lines = Vec::new();
+    let mut failed = false;
+    let mut describe = |store: &str, result: Option<Result<String, ImportError>>| match result {
+        Some(Ok(summary)) => lines.push(format!("{}: {}", store, summary)),
+        Some(Err(e)) => {
+            failed = true;
+            lines.push(format!("{}: {}", store, e));
+        }
+        None => {}
+    };
+    let index = report.index.map(|r| {
+        r.map(|entries| {
+            let summary = format!("{} 件を取り込みました", entries.len());
+            indexing::apply_rebuilt_index(&app, entries, show_icons);
+            summary
+        })
+    });
+    describe("インデックス", index);
+    describe(
+        "履歴",
+        report
+            .history
+            .map(|r| r.map(|count| format!("{} 件になりました", count))),
+    );
+    describe(
+        "ウィンドウ位置",
+        report
+            .window
+            .map(|r| r.map(|()| "次回表示から反映されます".to_string())),
+    );
+
+    if lines.is_empty() {
+        lines.push(format!(
+            "{} に {} / {} / {} がありません",
+            dir.display(),
+            export::INDEX_FILE,
+            export::HISTORY_FILE,
+            export::WINDOW_FILE
+        ));
+        failed = true;
+    }
+    let kind = if failed {
+        MessageDialogKind::Warning
+    } else {
+        MessageDialogKind::Info
+    };
+    show_message(&app, kind, lines.join("\n"));
+}
+
+fn show_message(app: &AppHandle, kind: MessageDialogKind, text: String) {
+    app.dialog()
+        .message(text)
+        .title("Snotra")
+        .kind(kind)
+        .show(|_| {});
+}
+
+/// Open a folder or file with its default handler (Explorer for folders).
+fn open_in_shell(target: &str) {
+    #[cfg(windows)]
+    {
+        use windows::core::HSTRING;
+        use windows::Win32::UI::Shell::ShellExecuteW;
+        use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+        unsafe {
+            ShellExecuteW(
+                None,
+                &HSTRING::from("open"),
+                &HSTRING::from(target),
+                None,
+                None,
+                SW_SHOWNORMAL,
+            );
+        }
+    }
+    #[cfg(not(windows))]
+    let _ = target;
+}
+
+/// A scan path with environment variables and `~` expanded, as the indexer scans it.
+#[tauri::command]
+pub fn expand_scan_path(path: String) -> String {
+    config::expand_path(&path)
+}
+
+#[tauri::command]
+pub fn get_scan_diagnostics() -> Option<ScanDiagnostics> {
+    diagnostics::load_diagnostics()
+}
+
 #[tauri::command]
 pub fn quit_app(app: AppHandle) {
     // Reuse the existing exit-requested listener (main.rs)
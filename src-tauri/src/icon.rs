@@ -90,6 +90,15 @@ impl IconCache {
         }
     }
 
+    /// Drop cached icons for the given paths (used when index entries change).
+    pub fn remove_paths<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) {
+        for path in paths {
+            if self.data.base64.remove(path).is_some() {
+                self.dirty = true;
+            }
+        }
+    }
+
     /// Clear all cached icons (used after index rebuild).
     pub fn clear(&mut self) {
         self.data.base64.clear();
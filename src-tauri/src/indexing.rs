@@ -1,14 +1,23 @@
+use std::cell::Cell;
 use std::sync::atomic::Ordering;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use snotra_core::indexer;
+use snotra_core::indexer::{self, AppEntry, CancelToken, RescanResult, ScanProgress};
 use snotra_core::search::SearchEngine;
+use snotra_core::watcher::{IndexWatcher, WatchOptions};
 use tauri::{AppHandle, Emitter, Manager};
 
 use crate::icon;
 use crate::platform::{PlatformBridge, PlatformCommand};
 use crate::state::AppState;
 
+const WATCH_DEBOUNCE_MS: u64 = 500;
+/// Minimum interval between `indexing-progress` events.
+const PROGRESS_INTERVAL_MS: u64 = 100;
+/// Index generation of the entries loaded at startup, which the startup rescan starts from.
+pub const STARTUP_GENERATION: u64 = 0;
+
 /// Start index build in a background thread.
 /// Returns `true` if the build was started, `false` if already running.
 pub fn start_index_build(app: &AppHandle) -> bool {
@@ -22,6 +31,11 @@ pub fn start_index_build(app: &AppHandle) -> bool {
     }
 
     state.indexing.store(true, Ordering::SeqCst);
+    // Stop the watcher so it cannot save changes based on the old snapshots during the
+    // build; it is restarted once the build finishes
+    *state.watcher.lock().unwrap() = None;
+    let cancel = CancelToken::new();
+    *state.index_cancel.lock().unwrap() = Some(cancel.clone());
 
     // Notify platform thread
     if let Some(bridge) = app.try_state::<Mutex<PlatformBridge>>()
@@ -33,43 +47,39 @@ pub fn start_index_build(app: &AppHandle) -> bool {
     std::thread::Builder::new()
         .name("snotra-index-build".to_string())
         .spawn(move || {
-            let (scan, show_hidden_system, show_icons) = {
+            let (paths, show_hidden_system, show_icons) = {
                 let state = app_handle.state::<AppState>();
                 let config = state.config.lock().unwrap();
                 (
-                    config.paths.scan.clone(),
+                    config.paths.clone(),
                     config.search.show_hidden_system,
                     config.appearance.show_icons,
                 )
             };
 
-            let entries = indexer::rebuild_and_save(&scan, show_hidden_system);
-
-            // Sync icon cache with current show_icons setting
-            {
-                let icon_state = app_handle.state::<icon::IconCacheState>();
-                let mut current = icon_state.lock().unwrap();
-                if show_icons {
-                    // Clear stale icons — re-extracted on next search
-                    if let Some(c) = current.as_mut() {
-                        c.clear();
-                    }
-                } else {
-                    // show_icons disabled — drop the cache entirely
-                    *current = None;
+            // Throttled so a fast scan does not flood the webviews
+            let last_progress: Cell<Option<Instant>> = Cell::new(None);
+            let on_progress = |progress: &ScanProgress| {
+                let interval = Duration::from_millis(PROGRESS_INTERVAL_MS);
+                if last_progress.get().is_some_and(|t| t.elapsed() < interval) {
+                    return;
                 }
-            }
+                last_progress.set(Some(Instant::now()));
+                let _ = app_handle.emit("indexing-progress", progress);
+            };
+            let rebuilt =
+                indexer::rebuild_and_save(&paths, show_hidden_system, &on_progress, &cancel);
 
-            // Update search engine
-            {
-                let state = app_handle.state::<AppState>();
-                let mut engine = state.engine.lock().unwrap();
-                *engine = SearchEngine::new(entries);
+            // Cancelled: keep the previous index and icons
+            let cancelled = rebuilt.is_none();
+            if let Some(entries) = rebuilt {
+                apply_rebuilt_index(&app_handle, entries, show_icons);
             }
 
             // Mark indexing complete
             {
                 let state = app_handle.state::<AppState>();
+                *state.index_cancel.lock().unwrap() = None;
                 state.indexing.store(false, Ordering::SeqCst);
             }
 
@@ -79,10 +89,170 @@ pub fn start_index_build(app: &AppHandle) -> bool {
                     b.send_command(PlatformCommand::SetIndexing(false));
                 }
 
+            // Re-arm the watcher with the current scan settings
+            restart_watcher(&app_handle);
+
             // Notify frontend
-            let _ = app_handle.emit("indexing-complete", ());
+            let event = if cancelled {
+                "indexing-cancelled"
+            } else {
+                "indexing-complete"
+            };
+            let _ = app_handle.emit(event, ());
         })
         .ok();
 
     true
 }
+
+/// Swap in the entries of a finished rebuild or an imported index.
+pub fn apply_rebuilt_index(app: &AppHandle, entries: Vec<AppEntry>, show_icons: bool) {
+    // Sync icon cache with current show_icons setting
+    {
+        let icon_state = app.state::<icon::IconCacheState>();
+        let mut current = icon_state.lock().unwrap();
+        if show_icons {
+            // Clear stale icons — re-extracted on next search
+            if let Some(c) = current.as_mut() {
+                c.clear();
+            }
+        } else {
+            // show_icons disabled — drop the cache entirely
+            *current = None;
+        }
+    }
+
+    // Update search engine
+    {
+        let state = app.state::<AppState>();
+        let custom = state.config.lock().unwrap().entries.custom.clone();
+        let mut engine = state.engine.lock().unwrap();
+        *engine = SearchEngine::new(indexer::merge_custom_entries(entries, &custom));
+        state.index_generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Swap the search engine for the result of the startup background rescan
+/// and emit `index-updated` with the diff.
+/// Skipped when an index build has started since, or when a rebuild, import or watcher
+/// update has already replaced the startup entries, as their data is newer.
+pub fn apply_rescan(app: &AppHandle, result: RescanResult) {
+    let state = app.state::<AppState>();
+    if state.index_build_started.load(Ordering::SeqCst) {
+        return;
+    }
+    apply_index_result(app, result, Some(STARTUP_GENERATION));
+}
+
+/// (Re)start the filesystem watcher from the current config, or stop it if disabled.
+pub fn restart_watcher(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let (paths, show_hidden_system, watch, interval_minutes) = {
+        let config = state.config.lock().unwrap();
+        (
+            config.paths.clone(),
+            config.search.show_hidden_system,
+            config.paths.watch,
+            config.paths.rescan_interval_minutes,
+        )
+    };
+
+    let mut current = state.watcher.lock().unwrap();
+    // Dropping the old watcher waits for its worker, so two workers never write the cache
+    // at once
+    *current = None;
+    if !watch {
+        return;
+    }
+
+    let app_handle = app.clone();
+    *current = Some(IndexWatcher::start(
+        paths,
+        show_hidden_system,
+        WatchOptions {
+            debounce: Duration::from_millis(WATCH_DEBOUNCE_MS),
+            fallback_interval: Duration::from_secs(u64::from(interval_minutes.max(1)) * 60),
+        },
+        move |result| {
+            // A running build will replace the whole index anyway
+            let state = app_handle.state::<AppState>();
+            if state.indexing.load(Ordering::SeqCst) {
+                return;
+            }
+            apply_index_result(&app_handle, result, None);
+        },
+    ));
+}
+
+/// Re-flatten the cached index after only scan path weights changed, falling back to a
+/// full build when no cache matches. The watcher is restarted so it flattens with the
+/// new weights too.
+pub fn apply_scan_weights(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let (paths, show_hidden_system, custom) = {
+        let config = state.config.lock().unwrap();
+        (
+            config.paths.clone(),
+            config.search.show_hidden_system,
+            config.entries.custom.clone(),
+        )
+    };
+    let Some(entries) = indexer::cached_entries(&paths, show_hidden_system) else {
+        state.index_build_started.store(false, Ordering::SeqCst);
+        start_index_build(app);
+        return;
+    };
+    {
+        let mut engine = state.engine.lock().unwrap();
+        *engine = SearchEngine::new(indexer::merge_custom_entries(entries, &custom));
+        state.index_generation.fetch_add(1, Ordering::SeqCst);
+    }
+    restart_watcher(app);
+}
+
+/// Rebuild the search engine after `[[entries.custom]]` changed, keeping scanned entries.
+pub fn apply_custom_entries(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let custom = state.config.lock().unwrap().entries.custom.clone();
+    let mut engine = state.engine.lock().unwrap();
+    let entries = engine.entries().to_vec();
+    *engine = SearchEngine::new(indexer::merge_custom_entries(entries, &custom));
+}
+
+/// Swap in a new entry set and emit `index-updated` with the diff.
+/// With `based_on`, the result is dropped unless the index is still at that generation.
+fn apply_index_result(app: &AppHandle, result: RescanResult, based_on: Option<u64>) {
+    if result.diff.is_empty() {
+        return;
+    }
+    let state = app.state::<AppState>();
+
+    {
+        let custom = state.config.lock().unwrap().entries.custom.clone();
+        let mut engine = state.engine.lock().unwrap();
+        // Checked under the engine lock so no other update can land in between
+        if based_on.is_some_and(|g| state.index_generation.load(Ordering::SeqCst) != g) {
+            return;
+        }
+        *engine = SearchEngine::new(indexer::merge_custom_entries(result.entries, &custom));
+        state.index_generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    // Drop icons for entries that disappeared or changed
+    {
+        let icon_state = app.state::<icon::IconCacheState>();
+        let mut current = icon_state.lock().unwrap();
+        if let Some(c) = current.as_mut() {
+            c.remove_paths(
+                result
+                    .diff
+                    .removed
+                    .iter()
+                    .chain(&result.diff.changed)
+                    .map(|e| e.target_path.as_str()),
+            );
+        }
+    }
+
+    let _ = app.emit("index-updated", &result.diff);
+}
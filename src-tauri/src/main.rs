@@ -89,12 +89,17 @@ fn main() {
     let is_first_run = Config::is_first_run();
     let config = Config::load();
 
+    // Startup rescan results arrive here and are applied once the app handle exists.
+    let (rescan_tx, rescan_rx) = std::sync::mpsc::channel();
     let (entries, initial_indexing) = if is_first_run {
         (Vec::new(), true)
     } else {
         let (entries, _) = indexer::load_or_scan(
-            &config.paths.scan,
+            &config.paths,
             config.search.show_hidden_system,
+            move |result| {
+                let _ = rescan_tx.send(result);
+            },
         );
         (entries, false)
     };
@@ -110,7 +115,10 @@ fn main() {
         config.appearance.max_history_display,
     );
 
-    let engine = SearchEngine::new(entries);
+    let engine = SearchEngine::new(indexer::merge_custom_entries(
+        entries,
+        &config.entries.custom,
+    ));
     let show_on_startup = config.general.show_on_startup;
     let show_tray = config.general.show_tray_icon;
     let ime_off = config.general.ime_off_on_show;
@@ -124,6 +132,9 @@ fn main() {
         config: Mutex::new(config),
         indexing: AtomicBool::new(initial_indexing),
         index_build_started: AtomicBool::new(false),
+        index_generation: AtomicU64::new(indexing::STARTUP_GENERATION),
+        index_cancel: Mutex::new(None),
+        watcher: Mutex::new(None),
     };
 
     tauri::Builder::default()
@@ -159,6 +170,11 @@ fn main() {
             commands::get_indexing_state,
             commands::list_system_fonts,
             commands::rebuild_index,
+            commands::cancel_index_build,
+            commands::get_scan_diagnostics,
+            commands::expand_scan_path,
+            commands::export_data,
+            commands::import_data,
             commands::quit_app,
         ])
         .setup(move |app| {
@@ -183,6 +199,21 @@ fn main() {
                 }
             }
 
+            // Apply the background rescan to the live engine when it finishes
+            let handle_for_rescan = app_handle.clone();
+            let _ = std::thread::Builder::new()
+                .name("snotra-rescan-apply".to_string())
+                .spawn(move || {
+                    if let Ok(result) = rescan_rx.recv() {
+                        indexing::apply_rescan(&handle_for_rescan, result);
+                    }
+                });
+
+            // Watch scan roots for live updates (first run starts it after the initial build)
+            if !is_first_run {
+                indexing::restart_watcher(&app_handle);
+            }
+
             // Start platform thread (hotkey, tray, IME)
             let platform = PlatformBridge::start(
                 app_handle.clone(),
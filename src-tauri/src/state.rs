@@ -1,9 +1,11 @@
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::sync::Mutex;
 
 use snotra_core::config::Config;
 use snotra_core::history::HistoryStore;
+use snotra_core::indexer::CancelToken;
 use snotra_core::search::SearchEngine;
+use snotra_core::watcher::IndexWatcher;
 
 pub struct AppState {
     pub engine: Mutex<SearchEngine>,
@@ -11,4 +13,10 @@ pub struct AppState {
     pub config: Mutex<Config>,
     pub indexing: AtomicBool,
     pub index_build_started: AtomicBool,
+    /// Bumped each time scanned entries are swapped into `engine`, so the startup rescan
+    /// can tell that newer data was applied while it ran.
+    pub index_generation: AtomicU64,
+    /// Cancels the running index build, if any.
+    pub index_cancel: Mutex<Option<CancelToken>>,
+    pub watcher: Mutex<Option<IndexWatcher>>,
 }
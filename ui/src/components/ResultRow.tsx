@@ -1,6 +1,25 @@
-import { type Component, Show, createSignal, createMemo, onMount } from "solid-js";
-import type { SearchResult } from "../lib/types";
+import { type Component, For, Show, createSignal, createMemo, onMount } from "solid-js";
+import type { EntryKind, SearchResult } from "../lib/types";
 import { truncatePath } from "../lib/truncatePath";
+import { highlightSegments } from "../lib/highlight";
+
+const KIND_LABELS: Record<EntryKind, string> = {
+  app_shortcut: "ショートカット",
+  executable: "実行ファイル",
+  document: "ファイル",
+  folder: "フォルダー",
+  url: "URL",
+  command: "コマンド",
+};
+
+const KIND_FALLBACK_ICONS: Record<EntryKind, string> = {
+  app_shortcut: "\u{1F4C4}",
+  executable: "\u{1F4C4}",
+  document: "\u{1F4C4}",
+  folder: "\u{1F4C1}",
+  url: "\u{1F310}",
+  command: "\u{2699}",
+};
 
 interface ResultRowProps {
   result: SearchResult;
@@ -37,6 +56,10 @@ const ResultRow: Component<ResultRowProps> = (props) => {
     return truncatePath(fullPath(), w, f);
   });
 
+  const segments = createMemo(() =>
+    highlightSegments(displayPath(), props.result.name, props.result.highlights),
+  );
+
   return (
     <div
       class="result-row"
@@ -45,12 +68,14 @@ const ResultRow: Component<ResultRowProps> = (props) => {
       onDblClick={props.onDoubleClick}
       onMouseEnter={props.onMouseEnter}
     >
-      <div class="result-icon">
+      <div class="result-icon" title={KIND_LABELS[props.result.kind]}>
         <Show
           when={props.icon}
           fallback={
             <span class="icon-fallback">
-              {props.result.isFolder ? "\u{1F4C1}" : "\u{1F4C4}"}
+              {props.result.isFolder
+                ? KIND_FALLBACK_ICONS.folder
+                : KIND_FALLBACK_ICONS[props.result.kind]}
             </span>
           }
         >
@@ -63,8 +88,23 @@ const ResultRow: Component<ResultRowProps> = (props) => {
         </Show>
       </div>
       <div class="result-text" ref={textRef}>
-        <div class="result-path-single">{displayPath()}</div>
+        <div class="result-path-single">
+          <For each={segments()}>
+            {(segment) =>
+              segment.hit ? (
+                <span class="result-highlight">{segment.text}</span>
+              ) : (
+                segment.text
+              )
+            }
+          </For>
+        </div>
       </div>
+      <Show when={props.result.description}>
+        <div class="result-description" title={props.result.description ?? ""}>
+          {props.result.description}
+        </div>
+      </Show>
     </div>
   );
 };
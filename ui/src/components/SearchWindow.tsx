@@ -18,8 +18,10 @@ import {
   activateSelected,
   refreshResults,
   indexing,
+  indexingProgress,
 } from "../stores/search";
 import { initCommands } from "../lib/commands";
+import * as api from "../lib/invoke";
 
 async function hideAllWindows() {
   getCurrentWindow().hide();
@@ -178,7 +180,24 @@ const SearchWindow: Component = () => {
     <div class="search-bar" data-tauri-drag-region onKeyDown={handleKeyDown}>
       <Show
         when={!indexing()}
-        fallback={<div class="indexing-message" data-tauri-drag-region>インデックス構築中...</div>}
+        fallback={
+          <div class="indexing-message" data-tauri-drag-region>
+            <span class="indexing-status" data-tauri-drag-region>
+              インデックス構築中...
+              <Show when={indexingProgress()}>
+                {(p) => (
+                  <span title={p().current_path}>
+                    {" "}
+                    {p().dirs_visited} フォルダ / {p().entries} 件
+                  </span>
+                )}
+              </Show>
+            </span>
+            <button class="indexing-cancel" onClick={() => void api.cancelIndexBuild()}>
+              中止
+            </button>
+          </div>
+        }
       >
         <input
           ref={setInputRef}
@@ -93,7 +93,7 @@ const SettingsGeneral: Component = () => {
           </SettingRow>
           <SettingRow
             label="表示時にIMEオフ"
-            description="ウィンドウ表示時にIMEを自動でオフにします"
+            description="ウィンドウ表示時にIMEを自動でオフにします。エントリ名のかなはローマ字でも検索できます（漢字は対象外）"
           >
             <ToggleSwitch
               checked={d().general.ime_off_on_show}
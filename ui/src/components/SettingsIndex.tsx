@@ -1,10 +1,32 @@
 import type { Component } from "solid-js";
-import { createEffect, createSignal, For, Show } from "solid-js";
+import {
+  createEffect,
+  createResource,
+  createSignal,
+  For,
+  onCleanup,
+  onMount,
+  Show,
+} from "solid-js";
+import { listen } from "@tauri-apps/api/event";
 import { open } from "@tauri-apps/plugin-dialog";
+import * as api from "../lib/invoke";
 import { draft, updateDraft } from "../stores/settings";
 import SettingRow from "./SettingRow";
 import ToggleSwitch from "./ToggleSwitch";
 
+/** Shows where a scan path with variables (`%USERPROFILE%`, `~`, ...) points on this machine. */
+const ExpandedPath: Component<{ path: string }> = (props) => {
+  const [expanded] = createResource(() => props.path, api.expandScanPath);
+  return (
+    <Show when={expanded() && expanded() !== props.path}>
+      <div class="scan-path-item-expanded" title={expanded()}>
+        {expanded()}
+      </div>
+    </Show>
+  );
+};
+
 const SettingsIndex: Component = () => {
   const d = () => draft()!;
 
@@ -12,6 +34,26 @@ const SettingsIndex: Component = () => {
   const [editPath, setEditPath] = createSignal("");
   const [editExtensions, setEditExtensions] = createSignal("");
   const [editIncludeFolders, setEditIncludeFolders] = createSignal(false);
+  const [editExclude, setEditExclude] = createSignal("");
+  const [editMaxDepth, setEditMaxDepth] = createSignal("");
+  const [editFollowLinks, setEditFollowLinks] = createSignal(true);
+  const [editPreferred, setEditPreferred] = createSignal(false);
+  const [editWeight, setEditWeight] = createSignal("0");
+  const [bookmarkPath, setBookmarkPath] = createSignal("");
+  const [diagnostics, { refetch: refetchDiagnostics }] = createResource(
+    api.getScanDiagnostics,
+  );
+
+  // The report is rewritten after every full or incremental scan
+  onMount(() => {
+    let unlisten: (() => void) | undefined;
+    void listen("indexing-complete", () => {
+      void refetchDiagnostics();
+    }).then((u) => {
+      unlisten = u;
+    });
+    onCleanup(() => unlisten?.());
+  });
 
   // Sync form fields when selection changes
   createEffect(() => {
@@ -20,38 +62,80 @@ const SettingsIndex: Component = () => {
       setEditPath("");
       setEditExtensions("");
       setEditIncludeFolders(false);
+      setEditExclude("");
+      setEditMaxDepth("");
+      setEditFollowLinks(true);
+      setEditPreferred(false);
+      setEditWeight("0");
     } else {
       const scan = d().paths.scan[idx];
       if (scan) {
         setEditPath(scan.path);
         setEditExtensions(scan.extensions.join(", "));
         setEditIncludeFolders(scan.include_folders);
+        setEditExclude(scan.exclude.join(", "));
+        setEditMaxDepth(scan.max_depth === null ? "" : String(scan.max_depth));
+        setEditFollowLinks(scan.follow_links);
+        setEditPreferred(scan.preferred);
+        setEditWeight(String(scan.weight));
       }
     }
   });
 
+  function splitList(value: string): string[] {
+    return value
+      .split(",")
+      .map((s) => s.trim())
+      .filter((s) => s.length > 0);
+  }
+
+  // Empty input means unlimited depth
+  function parseMaxDepth(value: string): number | null {
+    const depth = parseInt(value);
+    return isNaN(depth) || depth < 0 ? null : depth;
+  }
+
+  // Empty or invalid input means no bonus
+  function parseWeight(value: string): number {
+    const weight = parseInt(value);
+    return isNaN(weight) ? 0 : weight;
+  }
+
   function applyEdit() {
     const idx = selectedIndex();
     if (idx === null) return;
     updateDraft((c) => {
       c.paths.scan[idx].path = editPath();
-      c.paths.scan[idx].extensions = editExtensions()
-        .split(",")
-        .map((s) => s.trim())
-        .filter((s) => s.length > 0);
+      c.paths.scan[idx].extensions = splitList(editExtensions());
       c.paths.scan[idx].include_folders = editIncludeFolders();
+      c.paths.scan[idx].exclude = splitList(editExclude());
+      c.paths.scan[idx].max_depth = parseMaxDepth(editMaxDepth());
+      c.paths.scan[idx].follow_links = editFollowLinks();
+      c.paths.scan[idx].preferred = editPreferred();
+      c.paths.scan[idx].weight = parseWeight(editWeight());
     });
   }
 
   function addScanPath() {
     const path = editPath();
-    const extensions = editExtensions()
-      .split(",")
-      .map((s) => s.trim())
-      .filter((s) => s.length > 0);
+    const extensions = splitList(editExtensions());
     const includeFolders = editIncludeFolders();
+    const exclude = splitList(editExclude());
+    const maxDepth = parseMaxDepth(editMaxDepth());
+    const followLinks = editFollowLinks();
+    const preferred = editPreferred();
+    const weight = parseWeight(editWeight());
     updateDraft((c) => {
-      c.paths.scan.push({ path, extensions, include_folders: includeFolders });
+      c.paths.scan.push({
+        path,
+        extensions,
+        include_folders: includeFolders,
+        exclude,
+        max_depth: maxDepth,
+        follow_links: followLinks,
+        preferred,
+        weight,
+      });
     });
     // Select the newly added item
     setSelectedIndex(d().paths.scan.length - 1);
@@ -81,10 +165,57 @@ const SettingsIndex: Component = () => {
     }
   }
 
+  async function browseBookmarks() {
+    const selected = await open({
+      directory: false,
+      multiple: false,
+      defaultPath: bookmarkPath() || undefined,
+    });
+    if (selected !== null) {
+      setBookmarkPath(selected as string);
+    }
+  }
+
+  function addBookmarkSource() {
+    const path = bookmarkPath().trim();
+    if (path.length === 0) return;
+    updateDraft((c) => {
+      c.paths.bookmarks.push({ path });
+    });
+    setBookmarkPath("");
+  }
+
+  function removeBookmarkSource(idx: number) {
+    updateDraft((c) => {
+      c.paths.bookmarks.splice(idx, 1);
+    });
+  }
+
+  async function browseSteamDir() {
+    const selected = await open({
+      directory: true,
+      multiple: false,
+      defaultPath: d().paths.steam_dir || undefined,
+    });
+    if (selected !== null) {
+      setSteamDir(selected as string);
+    }
+  }
+
+  function setSteamDir(value: string) {
+    updateDraft((c) => {
+      c.paths.steam_dir = value.trim().length > 0 ? value : null;
+    });
+  }
+
   function formatExtensions(exts: string[]): string {
     return exts.join(", ");
   }
 
+  function formatScannedAt(secs: number): string {
+    return new Date(secs * 1000).toLocaleString("ja-JP");
+  }
+
   return (
     <div class="settings-section">
       <div class="settings-group">
@@ -166,9 +297,60 @@ const SettingsIndex: Component = () => {
         </div>
       </div>
 
+      <div class="settings-group">
+        <div class="settings-group-title">変更の監視</div>
+        <div class="settings-group-content">
+          <SettingRow
+            label="スキャンパスを監視"
+            description="ファイルの追加・削除・名前変更を即時にインデックスへ反映します"
+          >
+            <ToggleSwitch
+              checked={d().paths.watch}
+              onChange={(v) =>
+                updateDraft((c) => {
+                  c.paths.watch = v;
+                })
+              }
+            />
+          </SettingRow>
+          <SettingRow
+            label="再スキャン間隔（分）"
+            description="監視できないパスがある場合に定期再スキャンする間隔"
+          >
+            <input
+              type="number"
+              min="1"
+              max="1440"
+              value={d().paths.rescan_interval_minutes}
+              disabled={!d().paths.watch}
+              onInput={(e) =>
+                updateDraft((c) => {
+                  c.paths.rescan_interval_minutes =
+                    parseInt(e.currentTarget.value) || 10;
+                })
+              }
+              style={{ width: "80px" }}
+            />
+          </SettingRow>
+        </div>
+      </div>
+
       <div class="settings-group">
         <div class="settings-group-title">スキャンパス</div>
         <div class="settings-group-content">
+          <SettingRow
+            label="同じリンク先のショートカットをまとめる"
+            description="スタートメニューとデスクトップなど、同じ対象を開くエントリを 1 件にまとめます"
+          >
+            <ToggleSwitch
+              checked={d().paths.dedup_targets}
+              onChange={(v) =>
+                updateDraft((c) => {
+                  c.paths.dedup_targets = v;
+                })
+              }
+            />
+          </SettingRow>
           {/* List */}
           <div class="scan-path-list">
             <For each={d().paths.scan}>
@@ -179,6 +361,7 @@ const SettingsIndex: Component = () => {
                   onClick={() => setSelectedIndex(idx())}
                 >
                   <div class="scan-path-item-path">{scan.path || "(未設定)"}</div>
+                  <ExpandedPath path={scan.path} />
                   <div class="scan-path-item-meta">
                     <span class="scan-path-item-exts">
                       {formatExtensions(scan.extensions) || "(拡張子未指定)"}
@@ -186,6 +369,25 @@ const SettingsIndex: Component = () => {
                     <Show when={scan.include_folders}>
                       <span class="scan-path-item-folder-badge" title="フォルダを含む">&#x1F4C1;</span>
                     </Show>
+                    <Show when={scan.max_depth !== null}>
+                      <span class="scan-path-item-exts">深さ {scan.max_depth}</span>
+                    </Show>
+                    <Show when={!scan.follow_links}>
+                      <span class="scan-path-item-exts">リンクを辿らない</span>
+                    </Show>
+                    <Show when={scan.preferred}>
+                      <span class="scan-path-item-exts">重複時に優先</span>
+                    </Show>
+                    <Show when={scan.weight !== 0}>
+                      <span class="scan-path-item-exts">
+                        重み {scan.weight > 0 ? `+${scan.weight}` : scan.weight}
+                      </span>
+                    </Show>
+                    <Show when={scan.exclude.length > 0}>
+                      <span class="scan-path-item-exts" title={scan.exclude.join(", ")}>
+                        除外 {scan.exclude.length}件
+                      </span>
+                    </Show>
                   </div>
                 </div>
               )}
@@ -207,6 +409,7 @@ const SettingsIndex: Component = () => {
                   参照...
                 </button>
               </div>
+              <ExpandedPath path={editPath()} />
             </label>
             <label>
               拡張子 (カンマ区切り)
@@ -217,6 +420,34 @@ const SettingsIndex: Component = () => {
                 placeholder=".lnk, .exe"
               />
             </label>
+            <label>
+              除外パターン (カンマ区切り、gitignore 形式)
+              <input
+                type="text"
+                value={editExclude()}
+                onInput={(e) => setEditExclude(e.currentTarget.value)}
+                placeholder="node_modules, /build, *.tmp"
+              />
+            </label>
+            <label>
+              最大深さ (空欄で無制限、0 で直下のみ)
+              <input
+                type="number"
+                min="0"
+                value={editMaxDepth()}
+                onInput={(e) => setEditMaxDepth(e.currentTarget.value)}
+                style={{ width: "80px" }}
+              />
+            </label>
+            <label>
+              重み (検索順位の補正、1 で起動 2 回分、負の値で下げる)
+              <input
+                type="number"
+                value={editWeight()}
+                onInput={(e) => setEditWeight(e.currentTarget.value)}
+                style={{ width: "80px" }}
+              />
+            </label>
             <div class="scan-path-form-toggle">
               <ToggleSwitch
                 checked={editIncludeFolders()}
@@ -224,6 +455,20 @@ const SettingsIndex: Component = () => {
               />
               <span>フォルダを含める</span>
             </div>
+            <div class="scan-path-form-toggle">
+              <ToggleSwitch
+                checked={editFollowLinks()}
+                onChange={(v) => setEditFollowLinks(v)}
+              />
+              <span>シンボリックリンク・ジャンクションを辿る</span>
+            </div>
+            <div class="scan-path-form-toggle">
+              <ToggleSwitch
+                checked={editPreferred()}
+                onChange={(v) => setEditPreferred(v)}
+              />
+              <span>同じリンク先の重複があればこのパスのものを残す</span>
+            </div>
             <div class="scan-path-form-actions">
               <Show
                 when={selectedIndex() !== null}
@@ -248,6 +493,121 @@ const SettingsIndex: Component = () => {
           </div>
         </div>
       </div>
+
+      <div class="settings-group">
+        <div class="settings-group-title">スキャン診断</div>
+        <div class="settings-group-content">
+          <Show
+            when={diagnostics()}
+            fallback={<div class="scan-path-item-exts">スキャン結果はまだありません</div>}
+          >
+            {(report) => (
+              <>
+                <div class="scan-path-item-exts">
+                  最終スキャン: {formatScannedAt(report().scanned_at)}
+                </div>
+                <div class="scan-path-list">
+                  <For each={report().paths}>
+                    {(path) => (
+                      <div class="scan-path-item">
+                        <div class="scan-path-item-path">{path.path}</div>
+                        <div class="scan-path-item-meta">
+                          <span class="scan-path-item-exts">{path.entries}件</span>
+                          <span class="scan-path-item-exts">
+                            フォルダ {path.dirs_visited}
+                          </span>
+                          <span class="scan-path-item-exts">
+                            隠し {path.hidden_skipped}
+                          </span>
+                          <span class="scan-path-item-exts">{path.elapsed_ms} ms</span>
+                        </div>
+                        <For each={path.errors}>
+                          {(error) => (
+                            <div class="scan-diagnostics-error" title={error.message}>
+                              {error.permission_denied ? "アクセス拒否" : "読み込み失敗"}:{" "}
+                              {error.path}
+                            </div>
+                          )}
+                        </For>
+                      </div>
+                    )}
+                  </For>
+                </div>
+              </>
+            )}
+          </Show>
+          <div class="scan-path-form-actions">
+            <button onClick={() => void refetchDiagnostics()}>再読み込み</button>
+          </div>
+        </div>
+      </div>
+
+      <div class="settings-group">
+        <div class="settings-group-title">ブラウザのブックマーク</div>
+        <div class="settings-group-content">
+          <div class="scan-path-list">
+            <For each={d().paths.bookmarks}>
+              {(source, idx) => (
+                <div class="scan-path-item">
+                  <div class="scan-path-item-path">{source.path}</div>
+                  <div class="scan-path-item-meta">
+                    <button
+                      class="btn-danger"
+                      onClick={() => removeBookmarkSource(idx())}
+                    >
+                      削除
+                    </button>
+                  </div>
+                </div>
+              )}
+            </For>
+          </div>
+          <div class="scan-path-form">
+            <label>
+              Bookmarks ファイル (Chrome / Edge)
+              <div class="scan-path-input-row">
+                <input
+                  type="text"
+                  value={bookmarkPath()}
+                  onInput={(e) => setBookmarkPath(e.currentTarget.value)}
+                  placeholder="%LOCALAPPDATA%\Google\Chrome\User Data\Default\Bookmarks"
+                />
+                <button type="button" class="btn-browse" onClick={browseBookmarks}>
+                  参照...
+                </button>
+              </div>
+            </label>
+            <div class="scan-path-form-actions">
+              <button onClick={addBookmarkSource}>追加</button>
+            </div>
+          </div>
+        </div>
+      </div>
+
+      <div class="settings-group">
+        <div class="settings-group-title">Steam</div>
+        <div class="settings-group-content">
+          <div class="scan-path-form">
+            <label>
+              Steam のインストールフォルダ（空欄で無効）
+              <div class="scan-path-input-row">
+                <input
+                  type="text"
+                  value={d().paths.steam_dir ?? ""}
+                  onChange={(e) => setSteamDir(e.currentTarget.value)}
+                  placeholder="%ProgramFiles(x86)%\Steam"
+                />
+                <button type="button" class="btn-browse" onClick={browseSteamDir}>
+                  参照...
+                </button>
+              </div>
+            </label>
+            <Show when={d().paths.steam_dir}>
+              <ExpandedPath path={d().paths.steam_dir ?? ""} />
+            </Show>
+          </div>
+        </div>
+      </div>
     </div>
   );
 };
@@ -28,6 +28,33 @@ export const SLASH_COMMANDS: SlashCommand[] = [
       hideAllWindowsFn?.();
     },
   },
+  {
+    command: "/e",
+    label: "/e",
+    description: "インデックス・履歴・ウィンドウ位置を JSON に書き出す",
+    action: () => {
+      api.exportData();
+      hideAllWindowsFn?.();
+    },
+  },
+  {
+    command: "/im",
+    label: "/im",
+    description: "JSON を取り込む（履歴は統合）",
+    action: () => {
+      api.importData(false);
+      hideAllWindowsFn?.();
+    },
+  },
+  {
+    command: "/ir",
+    label: "/ir",
+    description: "JSON を取り込む（履歴は置き換え）",
+    action: () => {
+      api.importData(true);
+      hideAllWindowsFn?.();
+    },
+  },
   {
     command: "/q",
     label: "/q",
@@ -0,0 +1,39 @@
+export interface HighlightSegment {
+  text: string;
+  hit: boolean;
+}
+
+/**
+ * Split a displayed path into plain and highlighted runs.
+ *
+ * `highlights` are code point indices of `name`. They are shown only when the
+ * last segment of the path starts with the name (e.g. "Firefox" in
+ * "C:\...\Firefox.lnk"); URLs and commands are returned as one plain run.
+ */
+export function highlightSegments(
+  path: string,
+  name: string,
+  highlights: number[],
+): HighlightSegment[] {
+  const body = path.endsWith("\\") ? path.slice(0, -1) : path;
+  const start = body.lastIndexOf("\\") + 1;
+  if (highlights.length === 0 || name === "" || !body.startsWith(name, start)) {
+    return [{ text: path, hit: false }];
+  }
+
+  const hits = new Set(highlights);
+  const segments: HighlightSegment[] = [];
+  const push = (text: string, hit: boolean) => {
+    const last = segments[segments.length - 1];
+    if (last && last.hit === hit) {
+      last.text += text;
+    } else {
+      segments.push({ text, hit });
+    }
+  };
+
+  push(path.slice(0, start), false);
+  Array.from(name).forEach((ch, i) => push(ch, hits.has(i)));
+  push(path.slice(start + name.length), false);
+  return segments.filter((s) => s.text !== "");
+}
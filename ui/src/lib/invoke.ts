@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { Config, SearchResult } from "./types";
+import type { Config, ScanDiagnostics, SearchResult } from "./types";
 
 export async function search(query: string): Promise<SearchResult[]> {
   return invoke<SearchResult[]>("search", { query });
@@ -117,6 +117,26 @@ export async function rebuildIndex(): Promise<boolean> {
   return invoke<boolean>("rebuild_index");
 }
 
+export async function cancelIndexBuild(): Promise<boolean> {
+  return invoke<boolean>("cancel_index_build");
+}
+
+export async function exportData(): Promise<void> {
+  return invoke("export_data");
+}
+
+export async function importData(replaceHistory: boolean): Promise<void> {
+  return invoke("import_data", { replaceHistory });
+}
+
+export async function expandScanPath(path: string): Promise<string> {
+  return invoke<string>("expand_scan_path", { path });
+}
+
+export async function getScanDiagnostics(): Promise<ScanDiagnostics | null> {
+  return invoke<ScanDiagnostics | null>("get_scan_diagnostics");
+}
+
 export async function quitApp(): Promise<void> {
   return invoke("quit_app");
 }
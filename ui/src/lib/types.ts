@@ -1,8 +1,83 @@
+export type EntryKind =
+  | "app_shortcut"
+  | "executable"
+  | "document"
+  | "folder"
+  | "url"
+  | "command";
+
 export interface SearchResult {
   name: string;
   path: string;
   isFolder: boolean;
   isError: boolean;
+  description: string | null;
+  kind: EntryKind;
+  /** Code point indices of `name` matched by the query. */
+  highlights: number[];
+}
+
+export interface ShortcutInfo {
+  target: string | null;
+  arguments: string | null;
+  working_dir: string | null;
+  description: string | null;
+  icon_location: string | null;
+  icon_index: number;
+}
+
+export interface UrlShortcut {
+  url: string;
+  icon_file: string | null;
+  icon_index: number;
+}
+
+export interface IndexEntry {
+  name: string;
+  target_path: string;
+  is_folder: boolean;
+  kind: EntryKind;
+  size: number | null;
+  modified: number | null;
+  shortcut: ShortcutInfo | null;
+  url: UrlShortcut | null;
+  context: string | null;
+  aliases: string[];
+  keywords: string[];
+  weight?: number;
+  custom: boolean;
+}
+
+export interface ScanProgress {
+  dirs_visited: number;
+  entries: number;
+  current_path: string;
+}
+
+export interface ScanError {
+  path: string;
+  message: string;
+  permission_denied: boolean;
+}
+
+export interface ScanPathReport {
+  path: string;
+  entries: number;
+  dirs_visited: number;
+  hidden_skipped: number;
+  errors: ScanError[];
+  elapsed_ms: number;
+}
+
+export interface ScanDiagnostics {
+  scanned_at: number;
+  paths: ScanPathReport[];
+}
+
+export interface IndexDiff {
+  added: IndexEntry[];
+  removed: IndexEntry[];
+  changed: IndexEntry[];
 }
 
 export interface HotkeyConfig {
@@ -47,10 +122,36 @@ export interface ScanPath {
   path: string;
   extensions: string[];
   include_folders: boolean;
+  exclude: string[];
+  max_depth: number | null;
+  follow_links: boolean;
+  preferred: boolean;
+  weight: number;
+}
+
+export interface BookmarkSource {
+  path: string;
 }
 
 export interface PathsConfig {
   scan: ScanPath[];
+  bookmarks: BookmarkSource[];
+  steam_dir: string | null;
+  dedup_targets: boolean;
+  watch: boolean;
+  rescan_interval_minutes: number;
+}
+
+export interface CustomEntry {
+  name: string;
+  target: string;
+  args: string;
+  working_dir: string;
+  keywords: string[];
+}
+
+export interface EntriesConfig {
+  custom: CustomEntry[];
 }
 
 export interface Config {
@@ -60,4 +161,5 @@ export interface Config {
   visual: VisualConfig;
   paths: PathsConfig;
   search: SearchConfig;
+  entries: EntriesConfig;
 }
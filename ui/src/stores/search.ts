@@ -1,6 +1,6 @@
 import { createSignal, createEffect, on } from "solid-js";
 import { emit, listen } from "@tauri-apps/api/event";
-import type { SearchResult } from "../lib/types";
+import type { IndexDiff, ScanProgress, SearchResult } from "../lib/types";
 import * as api from "../lib/invoke";
 import { findCommand } from "../lib/commands";
 
@@ -11,6 +11,7 @@ const [results, setResults] = createSignal<SearchResult[]>([]);
 const [selected, setSelected] = createSignal(0);
 const [iconCache, setIconCache] = createSignal<Map<string, string>>(new Map());
 const [indexing, setIndexing] = createSignal(false);
+const [indexingProgress, setIndexingProgress] = createSignal<ScanProgress | null>(null);
 
 let debounceTimer: ReturnType<typeof setTimeout> | undefined;
 
@@ -199,8 +200,22 @@ async function initIndexingState() {
   const state = await api.getIndexingState();
   setIndexing(state);
 
-  listen("indexing-complete", () => {
-    setIndexing(false);
+  listen<ScanProgress>("indexing-progress", (event) => {
+    setIndexing(true);
+    setIndexingProgress(event.payload);
+  });
+
+  // A cancelled build keeps the previous index
+  for (const name of ["indexing-complete", "indexing-cancelled"]) {
+    listen(name, () => {
+      setIndexing(false);
+      setIndexingProgress(null);
+      refreshResults();
+    });
+  }
+
+  // Background rescan swapped the index; re-run the current query
+  listen<IndexDiff>("index-updated", () => {
     refreshResults();
   });
 }
@@ -224,5 +239,6 @@ export {
   refreshResults,
   resetForShow,
   indexing,
+  indexingProgress,
   initIndexingState,
 };